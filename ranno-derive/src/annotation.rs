@@ -0,0 +1,117 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse_quote, DeriveInput, Error, Field, Result, Type};
+
+use crate::fields;
+
+pub fn derive(input: DeriveInput) -> Result<TokenStream> {
+    let fields = fields::struct_fields(&input, "Annotation")?;
+
+    let mut children = Vec::<Type>::new();
+    let mut combine = false;
+
+    for attr in &input.attrs {
+        if !attr.path().is_ident("annotation") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("child") {
+                children.push(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("combine") {
+                combine = true;
+                Ok(())
+            } else {
+                Err(meta.error("unsupported `annotation` attribute"))
+            }
+        })?;
+    }
+
+    if children.is_empty() {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "at least one child must be given with \
+             `#[annotation(child = Type)]`",
+        ));
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) =
+        input.generics.split_for_impl();
+
+    let mut impls = Vec::new();
+
+    for child in &children {
+        let mut where_clause =
+            where_clause.cloned().unwrap_or_else(|| parse_quote!(where));
+        for field in fields {
+            let ty = &field.ty;
+            where_clause
+                .predicates
+                .push(parse_quote!(#ty: ::ranno::Annotation<#child>));
+        }
+
+        let body = fields::construct(fields, |_, field| {
+            let ty = &field.ty;
+            quote!(<#ty as ::ranno::Annotation<#child>>::from_child(t))
+        });
+
+        impls.push(quote! {
+            impl #impl_generics ::ranno::Annotation<#child>
+                for #ident #ty_generics #where_clause
+            {
+                fn from_child(t: &#child) -> Self {
+                    #body
+                }
+            }
+        });
+    }
+
+    for (member, field) in fields::members(fields) {
+        if !is_component(field)? {
+            continue;
+        }
+
+        let ty = &field.ty;
+        impls.push(quote! {
+            impl #impl_generics ::core::borrow::Borrow<#ty>
+                for #ident #ty_generics #where_clause
+            {
+                fn borrow(&self) -> &#ty {
+                    &self.#member
+                }
+            }
+        });
+    }
+
+    if combine {
+        impls.push(crate::combine::derive_for(&input, fields)?);
+    }
+
+    Ok(quote!(#(#impls)*))
+}
+
+/// Returns true if the field is marked with `#[annotation(component)]`.
+fn is_component(field: &Field) -> Result<bool> {
+    let mut component = false;
+    for attr in &field.attrs {
+        if !attr.path().is_ident("annotation") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("component") {
+                component = true;
+                Ok(())
+            } else {
+                Err(meta.error("unsupported `annotation` attribute"))
+            }
+        })?;
+    }
+    Ok(component)
+}
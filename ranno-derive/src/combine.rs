@@ -0,0 +1,119 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse_quote, DeriveInput, Field, Fields, Result, WhereClause};
+
+use crate::fields;
+
+/// How a field is combined.
+enum Strategy {
+    /// Using the field's own `Combine` implementation.
+    Combine,
+    /// Adding the fields, starting from the default value.
+    Sum,
+    /// Taking the largest field, starting from the smallest value.
+    Max,
+}
+
+impl Strategy {
+    fn of(field: &Field) -> Result<Self> {
+        let mut strategy = Self::Combine;
+        for attr in &field.attrs {
+            if !attr.path().is_ident("combine") {
+                continue;
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("sum") {
+                    strategy = Self::Sum;
+                    Ok(())
+                } else if meta.path.is_ident("max") {
+                    strategy = Self::Max;
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported `combine` attribute"))
+                }
+            })?;
+        }
+        Ok(strategy)
+    }
+}
+
+pub fn derive(input: DeriveInput) -> Result<TokenStream> {
+    let fields = fields::struct_fields(&input, "Combine")?;
+    derive_for(&input, fields)
+}
+
+/// Derive `Combine` for the given struct with the given fields.
+pub fn derive_for(input: &DeriveInput, fields: &Fields) -> Result<TokenStream> {
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) =
+        input.generics.split_for_impl();
+
+    let mut where_clause: WhereClause =
+        where_clause.cloned().unwrap_or_else(|| parse_quote!(where));
+
+    let mut strategies = Vec::new();
+    for field in fields {
+        let ty = &field.ty;
+        let strategy = Strategy::of(field)?;
+        where_clause.predicates.push(match strategy {
+            Strategy::Combine => parse_quote!(#ty: ::ranno::Combine),
+            Strategy::Sum => parse_quote!(
+                #ty: ::core::default::Default
+                    + ::core::clone::Clone
+                    + ::core::ops::Add<Output = #ty>
+            ),
+            Strategy::Max => parse_quote!(
+                #ty: ::ranno::combine::Minimum
+                    + ::core::clone::Clone
+                    + ::core::cmp::Ord
+            ),
+        });
+        strategies.push(strategy);
+    }
+
+    let mut identity_strategies = strategies.iter();
+    let identity = fields::construct(fields, |_, field| {
+        let ty = &field.ty;
+        match identity_strategies.next() {
+            Some(Strategy::Combine) => {
+                quote!(<#ty as ::ranno::Combine>::identity())
+            }
+            Some(Strategy::Max) => {
+                quote!(<#ty as ::ranno::combine::Minimum>::MIN)
+            }
+            _ => quote!(<#ty as ::core::default::Default>::default()),
+        }
+    });
+
+    let mut combine_strategies = strategies.iter();
+    let combine = fields::construct(fields, |member, _| {
+        let (this, other) = (quote!(self.#member), quote!(other.#member));
+        match combine_strategies.next() {
+            Some(Strategy::Sum) => quote!(#this.clone() + #other.clone()),
+            Some(Strategy::Max) => {
+                quote!(::core::cmp::Ord::max(&#this, &#other).clone())
+            }
+            _ => quote!(::ranno::Combine::combine(&#this, &#other)),
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::ranno::Combine for #ident #ty_generics
+            #where_clause
+        {
+            fn identity() -> Self {
+                #identity
+            }
+
+            fn combine(&self, other: &Self) -> Self {
+                #combine
+            }
+        }
+    })
+}
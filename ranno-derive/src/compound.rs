@@ -0,0 +1,335 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{
+    Data, DeriveInput, Error, Field, Fields, GenericArgument, PathArguments,
+    Result, Type,
+};
+
+use crate::fields;
+
+/// The way a field holds children of its node.
+enum Kind {
+    /// The field is a leaf.
+    Leaf,
+    /// The field is an annotated child node.
+    Node,
+    /// The field optionally holds an annotated child node.
+    OptionalNode,
+    /// The field holds a slice of annotated child nodes.
+    Nodes,
+}
+
+impl Kind {
+    fn of(field: &Field) -> Option<Self> {
+        let is = |name| field.attrs.iter().any(|a| a.path().is_ident(name));
+
+        if is("leaf") {
+            Some(Self::Leaf)
+        } else if fields::is_annotated(&field.ty) {
+            Some(Self::Node)
+        } else if is("child") {
+            match type_arg(&field.ty, "Option") {
+                Some(_) => Some(Self::OptionalNode),
+                None => Some(Self::Nodes),
+            }
+        } else {
+            None
+        }
+    }
+}
+
+/// The types the structure is a compound of.
+struct Types {
+    anno: Type,
+    leaf: Type,
+    node: Type,
+}
+
+impl Types {
+    fn of(input: &DeriveInput) -> Result<Self> {
+        let mut leaf = None;
+        for attr in &input.attrs {
+            if attr.path().is_ident("compound") {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("leaf") {
+                        leaf = Some(meta.value()?.parse()?);
+                        Ok(())
+                    } else {
+                        Err(meta.error("unsupported `compound` attribute"))
+                    }
+                })?;
+            }
+        }
+
+        let all_fields: Vec<&Field> = match &input.data {
+            Data::Struct(data) => data.fields.iter().collect(),
+            Data::Enum(data) => data
+                .variants
+                .iter()
+                .flat_map(|variant| &variant.fields)
+                .collect(),
+            Data::Union(_) => {
+                return Err(Error::new_spanned(
+                    &input.ident,
+                    "`Compound` can't be derived for unions",
+                ))
+            }
+        };
+
+        let mut annotated = None;
+        for field in &all_fields {
+            match Kind::of(field) {
+                Some(Kind::Leaf) => {
+                    leaf.get_or_insert_with(|| field.ty.clone());
+                }
+                Some(_) => {
+                    annotated = annotated.or_else(|| find_annotated(&field.ty));
+                }
+                None => {}
+            }
+        }
+
+        let (node, anno) = annotated.ok_or_else(|| {
+            Error::new_spanned(
+                &input.ident,
+                "no child nodes found, they must be held in `Annotated` \
+                 fields, or fields marked with `#[child]`",
+            )
+        })?;
+        let leaf = leaf.ok_or_else(|| {
+            Error::new_spanned(
+                &input.ident,
+                "no leaves found, a field must be marked with `#[leaf]`, or \
+                 the leaf type given with `#[compound(leaf = Type)]`",
+            )
+        })?;
+
+        Ok(Self { anno, leaf, node })
+    }
+}
+
+/// Returns the argument of a type with a single type argument and the given
+/// name, such as `Option<T>`.
+fn type_arg<'a>(ty: &'a Type, name: &str) -> Option<&'a Type> {
+    let Type::Path(ty) = ty else {
+        return None;
+    };
+    let segment = ty.path.segments.last()?;
+    if segment.ident != name {
+        return None;
+    }
+
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        GenericArgument::Type(arg) => Some(arg),
+        _ => None,
+    }
+}
+
+/// Returns the child and annotation types of the first `Annotated` found in
+/// the given type.
+fn find_annotated(ty: &Type) -> Option<(Type, Type)> {
+    let Type::Path(ty) = ty else {
+        return match ty {
+            Type::Array(ty) => find_annotated(&ty.elem),
+            Type::Slice(ty) => find_annotated(&ty.elem),
+            Type::Reference(ty) => find_annotated(&ty.elem),
+            _ => None,
+        };
+    };
+    let segment = ty.path.segments.last()?;
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+
+    let mut types = args.args.iter().filter_map(|arg| match arg {
+        GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    });
+
+    if segment.ident == "Annotated" {
+        let child = types.next()?.clone();
+        let anno = types.next()?.clone();
+        return Some((child, anno));
+    }
+    types.find_map(find_annotated)
+}
+
+/// Generate the body of `child` or `child_mut`, returning the child at
+/// `index` of the node `self`.
+fn child_body(input: &DeriveInput, mutable: bool) -> TokenStream {
+    let child_ref = match mutable {
+        true => quote!(::ranno::compound::ChildMut),
+        false => quote!(::ranno::compound::ChildRef),
+    };
+
+    let lookup = |binding: &TokenStream, kind: Kind| match kind {
+        Kind::Leaf => quote! {
+            if index == 0 {
+                return Some(#child_ref::Leaf(#binding));
+            }
+            index -= 1;
+        },
+        Kind::Node => quote! {
+            if index == 0 {
+                return Some(#child_ref::Node(#binding));
+            }
+            index -= 1;
+        },
+        Kind::OptionalNode => quote! {
+            if let Some(node) = #binding {
+                if index == 0 {
+                    return Some(#child_ref::Node(node));
+                }
+                index -= 1;
+            }
+        },
+        Kind::Nodes => {
+            let nodes = match mutable {
+                true => quote!(::core::convert::AsMut::<[_]>::as_mut(#binding)),
+                false => {
+                    quote!(::core::convert::AsRef::<[_]>::as_ref(#binding))
+                }
+            };
+            let node = match mutable {
+                true => quote!(&mut nodes[index]),
+                false => quote!(&nodes[index]),
+            };
+            quote! {
+                let nodes = #nodes;
+                let len = nodes.len();
+                if index < len {
+                    return Some(#child_ref::Node(#node));
+                }
+                index -= len;
+            }
+        }
+    };
+
+    // binds the fields holding children, returning the pattern binding them
+    // together with the lookups through them, in order
+    let bind = |path: TokenStream, fields: &Fields| {
+        let mut lookups = Vec::new();
+        let mut bindings = Vec::new();
+
+        for (i, field) in fields.iter().enumerate() {
+            let binding = format_ident!("__field{}", i);
+            match Kind::of(field) {
+                Some(kind) => {
+                    lookups.push(lookup(&quote!(#binding), kind));
+                    bindings.push(Some(binding));
+                }
+                None => bindings.push(None),
+            }
+        }
+
+        let pattern = match fields {
+            Fields::Named(_) => {
+                let bound = fields.iter().zip(&bindings).filter_map(
+                    |(field, binding)| {
+                        let ident = field.ident.as_ref()?;
+                        binding.as_ref().map(|b| quote!(#ident: #b))
+                    },
+                );
+                quote!(#path { #(#bound,)* .. })
+            }
+            Fields::Unnamed(_) => {
+                let bound = bindings.iter().map(|binding| match binding {
+                    Some(binding) => quote!(#binding),
+                    None => quote!(_),
+                });
+                quote!(#path(#(#bound),*))
+            }
+            Fields::Unit => quote!(#path),
+        };
+
+        (pattern, quote!(#(#lookups)*))
+    };
+
+    let arms = match &input.data {
+        Data::Struct(data) => vec![bind(quote!(Self), &data.fields)],
+        Data::Enum(data) => data
+            .variants
+            .iter()
+            .map(|variant| {
+                let name = &variant.ident;
+                bind(quote!(Self::#name), &variant.fields)
+            })
+            .collect(),
+        Data::Union(_) => Vec::new(),
+    };
+    let arms = arms.into_iter().map(|(pattern, lookups)| {
+        quote! {
+            #pattern => {
+                #lookups
+            }
+        }
+    });
+
+    quote! {
+        let mut index = index;
+        match self {
+            #(#arms)*
+        }
+        None
+    }
+}
+
+pub fn derive(input: DeriveInput) -> Result<TokenStream> {
+    let Types { anno, leaf, node } = Types::of(&input)?;
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) =
+        input.generics.split_for_impl();
+
+    let body = child_body(&input, false);
+
+    Ok(quote! {
+        impl #impl_generics ::ranno::compound::Compound<#anno>
+            for #ident #ty_generics #where_clause
+        {
+            type Leaf = #leaf;
+            type Node = #node;
+
+            #[allow(unused_mut, unused_variables, unreachable_code)]
+            fn child(
+                &self,
+                index: usize,
+            ) -> Option<::ranno::compound::ChildOf<'_, Self, #anno>> {
+                #body
+            }
+        }
+    })
+}
+
+pub fn derive_mut(input: DeriveInput) -> Result<TokenStream> {
+    let Types { anno, .. } = Types::of(&input)?;
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) =
+        input.generics.split_for_impl();
+
+    let body = child_body(&input, true);
+
+    Ok(quote! {
+        impl #impl_generics ::ranno::compound::CompoundMut<#anno>
+            for #ident #ty_generics #where_clause
+        {
+            #[allow(unused_mut, unused_variables, unreachable_code)]
+            fn child_mut(
+                &mut self,
+                index: usize,
+            ) -> Option<::ranno::compound::ChildMutOf<'_, Self, #anno>> {
+                #body
+            }
+        }
+    })
+}
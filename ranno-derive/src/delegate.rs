@@ -0,0 +1,84 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::punctuated::Punctuated;
+use syn::{parse_quote, DeriveInput, Error, Result, Token, Type};
+
+use crate::fields;
+
+pub fn derive(input: DeriveInput) -> Result<TokenStream> {
+    let fields = fields::struct_fields(&input, "AnnotationDelegate")?;
+
+    let mut annos = Vec::<Type>::new();
+    for attr in &input.attrs {
+        if attr.path().is_ident("delegate") {
+            annos.extend(attr.parse_args_with(
+                Punctuated::<Type, Token![,]>::parse_terminated,
+            )?);
+        }
+    }
+
+    if annos.is_empty() {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "at least one annotation must be given with \
+             `#[delegate(Type)]`",
+        ));
+    }
+
+    // the field delegated to is the only one, or the one marked with
+    // `#[delegate]`
+    let mut marked = fields::members(fields).filter(|(_, field)| {
+        field.attrs.iter().any(|a| a.path().is_ident("delegate"))
+    });
+    let (member, field) =
+        match (marked.next(), marked.next()) {
+            (Some(marked), None) => marked,
+            (Some(_), Some((_, field))) => {
+                return Err(Error::new_spanned(
+                    field,
+                    "only one field can be marked with `#[delegate]`",
+                ))
+            }
+            (None, _) if fields.len() == 1 => {
+                // unwrapping is ok since there is exactly one field
+                fields::members(fields).next().unwrap()
+            }
+            (None, _) => return Err(Error::new_spanned(
+                &input.ident,
+                "the field to delegate to must be marked with `#[delegate]`",
+            )),
+        };
+
+    let ident = &input.ident;
+    let ty = &field.ty;
+    let (impl_generics, ty_generics, where_clause) =
+        input.generics.split_for_impl();
+
+    let impls = annos.iter().map(|anno| {
+        let mut where_clause =
+            where_clause.cloned().unwrap_or_else(|| parse_quote!(where));
+        where_clause
+            .predicates
+            .push(parse_quote!(#anno: ::ranno::Annotation<#ty>));
+
+        quote! {
+            impl #impl_generics ::ranno::Annotation<#ident #ty_generics>
+                for #anno #where_clause
+            {
+                fn from_child(t: &#ident #ty_generics) -> Self {
+                    <#anno as ::ranno::Annotation<#ty>>::from_child(
+                        &t.#member,
+                    )
+                }
+            }
+        }
+    });
+
+    Ok(quote!(#(#impls)*))
+}
@@ -0,0 +1,61 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    Data, DeriveInput, Error, Field, Fields, Index, Member, Result, Type,
+};
+
+/// Returns the fields of a struct, failing for enums and unions.
+pub fn struct_fields<'a>(
+    input: &'a DeriveInput,
+    derive: &str,
+) -> Result<&'a Fields> {
+    match &input.data {
+        Data::Struct(data) => Ok(&data.fields),
+        _ => Err(Error::new_spanned(
+            input,
+            format!("`{derive}` can only be derived for structs"),
+        )),
+    }
+}
+
+/// Returns the member used to access each field.
+pub fn members(fields: &Fields) -> impl Iterator<Item = (Member, &Field)> {
+    fields.iter().enumerate().map(|(i, field)| {
+        let member = match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(Index::from(i)),
+        };
+        (member, field)
+    })
+}
+
+/// Build a struct with the given fields, each initialized with the
+/// expression computed for it.
+pub fn construct<F>(fields: &Fields, mut init: F) -> TokenStream
+where
+    F: FnMut(&Member, &Field) -> TokenStream,
+{
+    let inits = members(fields).map(|(member, field)| {
+        let expr = init(&member, field);
+        quote!(#member: #expr)
+    });
+    quote!(Self { #(#inits),* })
+}
+
+/// Returns true if the given type is an `Annotated`.
+pub fn is_annotated(ty: &Type) -> bool {
+    match ty {
+        Type::Path(ty) => ty
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Annotated"),
+        _ => false,
+    }
+}
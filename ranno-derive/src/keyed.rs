@@ -0,0 +1,44 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{DeriveInput, Error, Result};
+
+use crate::fields;
+
+pub fn derive(input: DeriveInput) -> Result<TokenStream> {
+    let fields = fields::struct_fields(&input, "Keyed")?;
+
+    let mut keys = fields::members(fields).filter(|(_, field)| {
+        field.attrs.iter().any(|a| a.path().is_ident("key"))
+    });
+
+    let (member, field) = keys.next().ok_or_else(|| {
+        Error::new_spanned(&input.ident, "a field must be marked with `#[key]`")
+    })?;
+    if let Some((_, field)) = keys.next() {
+        return Err(Error::new_spanned(
+            field,
+            "only one field can be marked with `#[key]`",
+        ));
+    }
+
+    let ident = &input.ident;
+    let ty = &field.ty;
+    let (impl_generics, ty_generics, where_clause) =
+        input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::ranno::anno::Keyed<#ty> for #ident #ty_generics
+            #where_clause
+        {
+            fn key(&self) -> &#ty {
+                &self.#member
+            }
+        }
+    })
+}
@@ -0,0 +1,390 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Derive macros for [`ranno`].
+//!
+//! The macros are re-exported by `ranno` under the `derive` feature, and
+//! should be used from there.
+//!
+//! [`ranno`]: https://docs.rs/ranno
+
+#![deny(clippy::all)]
+#![deny(missing_docs)]
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+mod annotation;
+mod combine;
+mod compound;
+mod delegate;
+mod fields;
+mod keyed;
+mod recursive;
+
+/// Derive `Annotation` for a struct whose fields are all annotations.
+///
+/// Since `Annotation` can't be implemented over any child, the children it is
+/// implemented over must be given using `#[annotation(child = Type)]`, which
+/// can be repeated. The annotation over a child is computed by computing each
+/// field over the same child.
+///
+/// With `#[annotation(combine)]`, `Combine` is also derived in the same way as
+/// with `#[derive(Combine)]`.
+///
+/// Fields marked with `#[annotation(component)]` can be borrowed from the
+/// struct, making them available as an `AnnoComponent`. Only one field of a
+/// given type can be marked.
+///
+/// # Example
+/// ```
+/// use ranno::anno::{Cardinality, Sum};
+/// use ranno::{Annotation, Combine};
+///
+/// struct Leaf(u64);
+///
+/// impl Annotation<Leaf> for Cardinality {
+///     fn from_child(_: &Leaf) -> Self {
+///         Cardinality::LEAF
+///     }
+/// }
+///
+/// impl Annotation<Leaf> for Sum<u64> {
+///     fn from_child(leaf: &Leaf) -> Self {
+///         Sum(leaf.0)
+///     }
+/// }
+///
+/// #[derive(Debug, PartialEq, ranno::Annotation)]
+/// #[annotation(child = Leaf, combine)]
+/// struct Stats {
+///     #[annotation(component)]
+///     count: Cardinality,
+///     sum: Sum<u64>,
+/// }
+///
+/// let a = Stats::from_child(&Leaf(2));
+/// let b = Stats::from_child(&Leaf(3));
+///
+/// let stats = a.combine(&b);
+///
+/// assert_eq!(stats.count, Cardinality(2));
+/// assert_eq!(stats.sum, Sum(5));
+///
+/// let count: &Cardinality = ranno::component::component(&stats);
+/// assert_eq!(*count, Cardinality(2));
+/// ```
+#[proc_macro_derive(Annotation, attributes(annotation, combine))]
+pub fn derive_annotation(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    annotation::derive(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derive `Combine` for a struct by combining each field separately.
+///
+/// By default, fields are combined using their own `Combine` implementation.
+/// This can be changed for fields that are not annotations themselves using:
+///
+/// - `#[combine(sum)]` to add them, starting from their default value.
+/// - `#[combine(max)]` to take the largest of them, starting from their
+///   smallest value as given by `ranno::combine::Minimum`.
+///
+/// # Example
+/// ```
+/// use ranno::anno::Cardinality;
+/// use ranno::Combine;
+///
+/// #[derive(Debug, PartialEq, Combine)]
+/// struct Summary {
+///     count: Cardinality,
+///     #[combine(sum)]
+///     total: u64,
+///     #[combine(max)]
+///     largest: u64,
+/// }
+///
+/// let a = Summary {
+///     count: Cardinality(1),
+///     total: 3,
+///     largest: 3,
+/// };
+/// let b = Summary {
+///     count: Cardinality(2),
+///     total: 7,
+///     largest: 5,
+/// };
+///
+/// let summary = Summary {
+///     count: Cardinality(3),
+///     total: 10,
+///     largest: 5,
+/// };
+///
+/// assert_eq!(a.combine(&b), summary);
+/// assert_eq!(Summary::identity().combine(&a), a);
+/// ```
+#[proc_macro_derive(Combine, attributes(combine))]
+pub fn derive_combine(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    combine::derive(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derive `Annotation` for a wrapper struct by delegating to the annotation
+/// over one of its fields.
+///
+/// The annotations to delegate must be given using `#[delegate(Type, ..)]`,
+/// since `Annotation` can't be implemented for any annotation. The field
+/// delegated to is the only field of the struct, or the one marked with
+/// `#[delegate]`.
+///
+/// # Example
+/// ```
+/// use ranno::anno::{Cardinality, Sum};
+/// use ranno::Annotation;
+///
+/// struct Node(Vec<u64>);
+///
+/// impl Annotation<Node> for Cardinality {
+///     fn from_child(node: &Node) -> Self {
+///         Cardinality(node.0.len() as u64)
+///     }
+/// }
+///
+/// impl Annotation<Node> for Sum<u64> {
+///     fn from_child(node: &Node) -> Self {
+///         Sum(node.0.iter().sum())
+///     }
+/// }
+///
+/// #[derive(ranno::AnnotationDelegate)]
+/// #[delegate(Cardinality, Sum<u64>)]
+/// struct Page(Node);
+///
+/// let page = Page(Node(vec![1, 2, 3]));
+///
+/// assert_eq!(Cardinality::from_child(&page), Cardinality(3));
+/// assert_eq!(Sum::from_child(&page), Sum(6));
+/// ```
+#[proc_macro_derive(AnnotationDelegate, attributes(delegate))]
+pub fn derive_annotation_delegate(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    delegate::derive(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derive `Keyed` for a struct, using the field marked with `#[key]` as its
+/// key.
+///
+/// # Example
+/// ```
+/// use ranno::anno::Keyed;
+///
+/// #[derive(Keyed)]
+/// struct Entry {
+///     #[key]
+///     id: u64,
+///     value: String,
+/// }
+///
+/// let entry = Entry {
+///     id: 42,
+///     value: String::from("value"),
+/// };
+///
+/// assert_eq!(entry.key(), &42);
+/// ```
+#[proc_macro_derive(Keyed, attributes(key))]
+pub fn derive_keyed(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    keyed::derive(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derive `Compound` for a recursive structure, allowing it to be traversed
+/// without hand-written glue.
+///
+/// The children of each variant of an enum, or of a struct, are its fields
+/// holding leaves or child nodes, in order:
+///
+/// - Fields whose type is `Annotated` are child nodes.
+/// - Fields marked with `#[child]` hold child nodes. If their type is an
+///   `Option`, they hold at most one, and otherwise a sequence of them,
+///   accessed as a slice through `AsRef`, such as a `Vec` or an array.
+/// - Fields marked with `#[leaf]` are leaves.
+///
+/// The annotation and node types are taken from the first `Annotated` found,
+/// and the leaf type from the first leaf. When there are no leaf fields, the
+/// leaf type must be given using `#[compound(leaf = Type)]`.
+///
+/// The markers are the same as the ones of [`macro@annotation`], allowing
+/// both to be used on the same structure.
+///
+/// # Example
+/// ```
+/// use ranno::anno::Cardinality;
+/// use ranno::walk::Nth;
+/// use ranno::{Annotated, Annotation, Branch, Compound};
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Leaf(u64);
+///
+/// impl Annotation<Leaf> for Cardinality {
+///     fn from_child(_: &Leaf) -> Self {
+///         Cardinality::LEAF
+///     }
+/// }
+///
+/// #[ranno::annotation(Cardinality)]
+/// #[derive(Compound)]
+/// enum Tree {
+///     Leaf(#[leaf] Leaf),
+///     Node(#[child] Vec<Annotated<Tree, Cardinality>>),
+/// }
+///
+/// let leaf = |n| Annotated::new(Tree::Leaf(Leaf(n)));
+/// let tree = Tree::Node(vec![
+///     Annotated::new(Tree::Node(vec![leaf(10), leaf(11)])),
+///     Annotated::new(Tree::Node(vec![leaf(12), leaf(13), leaf(14)])),
+/// ]);
+///
+/// assert_eq!(tree.children().count(), 2);
+///
+/// let branch = Branch::walk(&tree, Nth::new(3)).unwrap();
+/// assert_eq!(*branch, Leaf(13));
+///
+/// assert!(Branch::walk(&tree, Nth::new(5)).is_none());
+/// ```
+#[proc_macro_derive(Compound, attributes(compound, child, leaf))]
+pub fn derive_compound(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    compound::derive(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derive `CompoundMut` for a recursive structure, allowing it to be mutably
+/// traversed.
+///
+/// The children are found in the same way as with [`macro@Compound`], which
+/// must also be derived or implemented.
+///
+/// # Example
+/// ```
+/// use ranno::anno::Cardinality;
+/// use ranno::compound::CompoundMut;
+/// use ranno::walk::Nth;
+/// use ranno::{Annotated, Annotation, BranchMut, Compound};
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Leaf(u64);
+///
+/// impl Annotation<Leaf> for Cardinality {
+///     fn from_child(_: &Leaf) -> Self {
+///         Cardinality::LEAF
+///     }
+/// }
+///
+/// #[ranno::annotation(Cardinality)]
+/// #[derive(Compound, CompoundMut)]
+/// struct Node {
+///     #[leaf]
+///     leaf: Leaf,
+///     #[child]
+///     left: Option<Annotated<Box<Node>, Cardinality>>,
+///     #[child]
+///     right: Option<Annotated<Box<Node>, Cardinality>>,
+/// }
+///
+/// let node = |n| Node {
+///     leaf: Leaf(n),
+///     left: None,
+///     right: None,
+/// };
+/// let mut tree = Node {
+///     leaf: Leaf(2),
+///     left: Some(Annotated::new(Box::new(node(1)))),
+///     right: Some(Annotated::new(Box::new(node(3)))),
+/// };
+///
+/// let mut branch = BranchMut::walk(&mut tree, Nth::new(2)).unwrap();
+/// *branch = Leaf(4);
+/// drop(branch);
+///
+/// let leaves: Vec<_> = tree.iter().collect();
+/// assert_eq!(leaves, [&Leaf(2), &Leaf(1), &Leaf(4)]);
+/// ```
+#[proc_macro_derive(CompoundMut, attributes(compound, child, leaf))]
+pub fn derive_compound_mut(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    compound::derive_mut(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Generate an `Annotation` implementation over a recursive structure, by
+/// combining the annotations over its children.
+///
+/// The attribute takes the annotation type to implement, which must
+/// implement `Combine`, and optionally:
+///
+/// - `for = Type` to give the child type, when it differs from the annotated
+///   type, e.g. because the annotation is one of its type parameters.
+/// - `leaf = path` to give a function computing the annotation over leaves.
+///
+/// The annotation over each variant of an enum, or over a struct, is
+/// computed by combining the annotations over its children and leaves, in
+/// order. Fields whose type is `Annotated` are children, and other fields can
+/// be marked as children with `#[child]`. Fields marked with `#[leaf]` are
+/// leaves, whose annotation is computed using the `leaf` function, or the
+/// `Annotation` implementation over them if no function is given.
+///
+/// # Example
+/// ```
+/// use std::rc::Rc;
+///
+/// use ranno::anno::Cardinality;
+/// use ranno::{Annotated, Annotation};
+///
+/// #[ranno::annotation(
+///     Cardinality,
+///     for = LinkedList<T, Cardinality>,
+///     leaf = Cardinality::from_leaf
+/// )]
+/// enum LinkedList<T, A> {
+///     Empty,
+///     Node {
+///         #[leaf]
+///         elem: T,
+///         next: Annotated<Rc<LinkedList<T, A>>, A>,
+///     },
+/// }
+///
+/// let list = LinkedList::<_, Cardinality>::Empty;
+/// let list = LinkedList::Node {
+///     elem: 1,
+///     next: Annotated::new(Rc::new(list)),
+/// };
+/// let list = LinkedList::Node {
+///     elem: 2,
+///     next: Annotated::new(Rc::new(list)),
+/// };
+///
+/// assert_eq!(Cardinality::from_child(&list), Cardinality(2));
+/// ```
+#[proc_macro_attribute]
+pub fn annotation(args: TokenStream, input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args as recursive::Args);
+    let input = parse_macro_input!(input as DeriveInput);
+    recursive::expand(args, input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
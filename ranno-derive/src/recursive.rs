@@ -0,0 +1,304 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use std::collections::HashSet;
+
+use proc_macro2::{Ident, TokenStream, TokenTree};
+use quote::{format_ident, quote, ToTokens};
+use syn::parse::{Parse, ParseStream};
+use syn::{
+    Data, DeriveInput, Error, Field, Fields, GenericParam, Generics, Path,
+    Result, Token, Type, WherePredicate,
+};
+
+use crate::fields;
+
+/// Arguments of the attribute.
+pub struct Args {
+    anno: Type,
+    child: Option<Type>,
+    leaf: Option<Path>,
+}
+
+impl Parse for Args {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let anno = input.parse()?;
+        let mut child = None;
+        let mut leaf = None;
+
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+
+            if input.peek(Token![for]) {
+                input.parse::<Token![for]>()?;
+                input.parse::<Token![=]>()?;
+                child = Some(input.parse()?);
+            } else {
+                let key: Ident = input.parse()?;
+                if key != "leaf" {
+                    return Err(Error::new_spanned(
+                        key,
+                        "expected `for` or `leaf`",
+                    ));
+                }
+                input.parse::<Token![=]>()?;
+                leaf = Some(input.parse()?);
+            }
+        }
+
+        Ok(Self { anno, child, leaf })
+    }
+}
+
+/// The role a field plays in the annotation of its parent.
+enum Role {
+    /// The field is not annotated.
+    None,
+    /// The field is a child, whose annotation is combined into its parent's.
+    Child,
+    /// The field is a leaf, whose annotation is combined into its parent's.
+    Leaf,
+}
+
+impl Role {
+    fn of(field: &Field) -> Self {
+        let is = |name| field.attrs.iter().any(|a| a.path().is_ident(name));
+
+        if is("leaf") {
+            Self::Leaf
+        } else if is("child") || fields::is_annotated(&field.ty) {
+            Self::Child
+        } else {
+            Self::None
+        }
+    }
+}
+
+pub fn expand(args: Args, mut input: DeriveInput) -> Result<TokenStream> {
+    let Args { anno, child, leaf } = args;
+
+    let ident = &input.ident;
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+
+    let (child, generics) = match child {
+        Some(child) => {
+            let generics = used_generics(&input.generics, &child);
+            (child, generics)
+        }
+        None => (
+            syn::parse_quote!(#ident #ty_generics),
+            input.generics.clone(),
+        ),
+    };
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    let combine = |tokens: &mut Vec<TokenStream>, role, value: TokenStream| {
+        let anno = match (role, &leaf) {
+            (Role::Leaf, Some(leaf)) => quote!((#leaf)(#value)),
+            _ => quote!(<#anno as ::ranno::Annotation<_>>::from_child(#value)),
+        };
+        tokens.push(quote! {
+            acc = ::ranno::Combine::combine(&acc, &#anno);
+        });
+    };
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let mut tokens = Vec::new();
+            for (member, field) in fields::members(&data.fields) {
+                let role = Role::of(field);
+                if !matches!(role, Role::None) {
+                    combine(&mut tokens, role, quote!(&t.#member));
+                }
+            }
+            quote!(#(#tokens)*)
+        }
+        Data::Enum(data) => {
+            let arms = data.variants.iter().map(|variant| {
+                let name = &variant.ident;
+                let mut tokens = Vec::new();
+
+                let pattern = match &variant.fields {
+                    Fields::Named(named) => {
+                        let mut bound = Vec::new();
+                        for field in &named.named {
+                            let role = Role::of(field);
+                            if !matches!(role, Role::None) {
+                                let field = field.ident.as_ref().unwrap();
+                                combine(&mut tokens, role, quote!(#field));
+                                bound.push(field);
+                            }
+                        }
+                        quote!({ #(#bound,)* .. })
+                    }
+                    Fields::Unnamed(unnamed) => {
+                        let bindings = unnamed.unnamed.iter().enumerate().map(
+                            |(i, field)| {
+                                let role = Role::of(field);
+                                if matches!(role, Role::None) {
+                                    quote!(_)
+                                } else {
+                                    let binding = format_ident!("__field{}", i);
+                                    combine(
+                                        &mut tokens,
+                                        role,
+                                        quote!(#binding),
+                                    );
+                                    quote!(#binding)
+                                }
+                            },
+                        );
+                        let bindings: Vec<_> = bindings.collect();
+                        quote!((#(#bindings),*))
+                    }
+                    Fields::Unit => quote!(),
+                };
+
+                quote! {
+                    #ident::#name #pattern => {
+                        #(#tokens)*
+                    }
+                }
+            });
+            quote! {
+                match t {
+                    #(#arms)*
+                }
+            }
+        }
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "annotations can't be generated for unions",
+            ))
+        }
+    };
+
+    let expanded = quote! {
+        impl #impl_generics ::ranno::Annotation<#child> for #anno
+            #where_clause
+        {
+            #[allow(unused_mut)]
+            fn from_child(t: &#child) -> Self {
+                let mut acc = <#anno as ::ranno::Combine>::identity();
+                #body
+                acc
+            }
+        }
+    };
+
+    // the markers are only removed when no other annotation, nor a derived
+    // `Compound`, needs them
+    let stacked = input.attrs.iter().any(|attr| {
+        attr.path()
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "annotation")
+    });
+    if !stacked && !derives_compound(&input) {
+        strip_markers(&mut input.data);
+    }
+
+    Ok(quote! {
+        #input
+        #expanded
+    })
+}
+
+/// Keep only the generic parameters mentioned by the given type, together
+/// with the predicates on them.
+fn used_generics(generics: &Generics, ty: &Type) -> Generics {
+    let mut idents = HashSet::new();
+    collect_idents(ty.to_token_stream(), &mut idents);
+
+    let mut used = generics.clone();
+    used.params = generics
+        .params
+        .iter()
+        .filter(|param| match param {
+            GenericParam::Type(param) => idents.contains(&param.ident),
+            GenericParam::Const(param) => idents.contains(&param.ident),
+            GenericParam::Lifetime(_) => true,
+        })
+        .cloned()
+        .collect();
+
+    if let Some(where_clause) = &mut used.where_clause {
+        where_clause.predicates = where_clause
+            .predicates
+            .iter()
+            .filter(|predicate| match predicate {
+                WherePredicate::Type(predicate) => {
+                    let mut mentioned = HashSet::new();
+                    collect_idents(
+                        predicate.bounded_ty.to_token_stream(),
+                        &mut mentioned,
+                    );
+                    generics.type_params().all(|param| {
+                        !mentioned.contains(&param.ident)
+                            || idents.contains(&param.ident)
+                    })
+                }
+                _ => true,
+            })
+            .cloned()
+            .collect();
+    }
+
+    used
+}
+
+fn collect_idents(tokens: TokenStream, idents: &mut HashSet<Ident>) {
+    for token in tokens {
+        match token {
+            TokenTree::Ident(ident) => {
+                idents.insert(ident);
+            }
+            TokenTree::Group(group) => collect_idents(group.stream(), idents),
+            _ => {}
+        }
+    }
+}
+
+fn derives_compound(input: &DeriveInput) -> bool {
+    input.attrs.iter().any(|attr| {
+        let mut derives = false;
+        if attr.path().is_ident("derive") {
+            // unparsable derives are left for the compiler to report
+            let _ = attr.parse_nested_meta(|meta| {
+                derives |= meta.path.segments.last().is_some_and(|segment| {
+                    segment.ident == "Compound"
+                        || segment.ident == "CompoundMut"
+                });
+                Ok(())
+            });
+        }
+        derives
+    })
+}
+
+fn strip_markers(data: &mut Data) {
+    let strip = |fields: &mut Fields| {
+        for field in fields.iter_mut() {
+            field.attrs.retain(|attr| {
+                !attr.path().is_ident("child") && !attr.path().is_ident("leaf")
+            });
+        }
+    };
+
+    match data {
+        Data::Struct(data) => strip(&mut data.fields),
+        Data::Enum(data) => {
+            for variant in &mut data.variants {
+                strip(&mut variant.fields);
+            }
+        }
+        Data::Union(_) => {}
+    }
+}
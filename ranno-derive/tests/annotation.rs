@@ -0,0 +1,121 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use std::borrow::Borrow;
+
+use ranno::anno::{Cardinality, Sum};
+use ranno::component::{self, AnnoComponent};
+use ranno::Annotation;
+
+struct Leaf(u64);
+
+struct Node(Vec<Leaf>);
+
+impl Annotation<Leaf> for Cardinality {
+    fn from_child(_: &Leaf) -> Self {
+        Cardinality::LEAF
+    }
+}
+
+impl Annotation<Leaf> for Sum<u64> {
+    fn from_child(leaf: &Leaf) -> Self {
+        Sum(leaf.0)
+    }
+}
+
+impl Annotation<Node> for Cardinality {
+    fn from_child(node: &Node) -> Self {
+        Cardinality(node.0.len() as u64)
+    }
+}
+
+impl Annotation<Node> for Sum<u64> {
+    fn from_child(node: &Node) -> Self {
+        Sum(node.0.iter().map(|leaf| leaf.0).sum())
+    }
+}
+
+#[derive(Debug, PartialEq, Annotation)]
+#[annotation(child = Leaf)]
+#[annotation(child = Node)]
+struct Pair(Cardinality, Sum<u64>);
+
+#[test]
+fn tuple_struct_is_annotated_over_every_child() {
+    assert_eq!(Pair::from_child(&Leaf(3)), Pair(Cardinality(1), Sum(3)));
+
+    let node = Node(vec![Leaf(1), Leaf(2)]);
+    assert_eq!(Pair::from_child(&node), Pair(Cardinality(2), Sum(3)));
+}
+
+#[derive(Debug, PartialEq, Annotation)]
+#[annotation(child = Node)]
+struct Both<A, B> {
+    first: A,
+    second: B,
+}
+
+#[test]
+fn generic_struct_is_annotated_by_its_parameters() {
+    let node = Node(vec![Leaf(4), Leaf(5)]);
+
+    let both = Both::<Cardinality, Sum<u64>>::from_child(&node);
+    assert_eq!(
+        both,
+        Both {
+            first: Cardinality(2),
+            second: Sum(9),
+        }
+    );
+
+    let both = Both::<Sum<u64>, Sum<u64>>::from_child(&node);
+    assert_eq!(both.first, both.second);
+}
+
+#[derive(Debug, PartialEq, Annotation)]
+#[annotation(child = Leaf)]
+struct Stats(#[annotation(component)] Cardinality, Sum<u64>);
+
+fn count<A, I>(anno: &A) -> u64
+where
+    A: AnnoComponent<Cardinality, I>,
+{
+    component::component::<Cardinality, _, _>(anno).0
+}
+
+#[test]
+fn components_are_borrowed() {
+    let stats = Stats::from_child(&Leaf(5));
+    assert_eq!(stats.1, Sum(5));
+
+    let cardinality: &Cardinality = stats.borrow();
+    assert_eq!(cardinality, &Cardinality(1));
+    assert_eq!(count(&stats), 1);
+
+    // components are found when nested in tuples as well
+    assert_eq!(count(&(Sum(3u64), stats)), 1);
+}
+
+#[derive(Debug, PartialEq, Annotation)]
+#[annotation(child = Node)]
+struct Tagged<T> {
+    #[annotation(component)]
+    tag: T,
+    count: Cardinality,
+}
+
+#[test]
+fn generic_components_are_borrowed() {
+    let tagged = Tagged::<Sum<u64>>::from_child(&Node(vec![Leaf(2), Leaf(3)]));
+
+    assert_eq!(tagged.count, Cardinality(2));
+    assert_eq!(component::component::<Sum<u64>, _, _>(&tagged), &Sum(5));
+
+    // a generic component can't be marked together with a concrete one, as
+    // their implementations could overlap
+    let tagged = Tagged::<Cardinality>::from_child(&Node(vec![Leaf(2)]));
+    assert_eq!(count(&tagged), 1);
+}
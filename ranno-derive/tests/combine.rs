@@ -0,0 +1,65 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use ranno::anno::Cardinality;
+use ranno::Combine;
+
+#[derive(Debug, PartialEq, Combine)]
+struct Pair(Cardinality, #[combine(max)] u32);
+
+#[test]
+fn tuple_struct_combines_each_field() {
+    let a = Pair(Cardinality(1), 7);
+    let b = Pair(Cardinality(2), 3);
+
+    assert_eq!(Pair::identity(), Pair(Cardinality(0), 0));
+    assert_eq!(a.combine(&b), Pair(Cardinality(3), 7));
+    assert_eq!(b.combine(&a), a.combine(&b));
+}
+
+#[derive(Debug, PartialEq, Combine)]
+struct Totals<T> {
+    count: Cardinality,
+    #[combine(sum)]
+    total: T,
+    #[combine(max)]
+    largest: T,
+}
+
+fn totals<T: Clone>(count: u64, total: T, largest: T) -> Totals<T> {
+    Totals {
+        count: Cardinality(count),
+        total,
+        largest,
+    }
+}
+
+#[test]
+fn generic_struct_combines_with_its_parameters() {
+    let a = totals(1u64, 3, 3);
+    let b = totals(2, 7, 5);
+    assert_eq!(a.combine(&b), totals(3, 10, 5));
+    assert_eq!(Totals::identity().combine(&a), a);
+
+    let a = totals(1, -3i32, -3);
+    let b = totals(1, -4, -4);
+    assert_eq!(a.combine(&b), totals(2, -7, -3));
+}
+
+#[derive(Debug, PartialEq, Combine)]
+struct Highest {
+    #[combine(max)]
+    highest: i64,
+}
+
+#[test]
+fn max_identity_is_the_smallest_value() {
+    let a = Highest { highest: -5 };
+
+    assert_eq!(Highest::identity(), Highest { highest: i64::MIN });
+    assert_eq!(Highest::identity().combine(&a), a);
+    assert_eq!(a.combine(&Highest::identity()), a);
+}
@@ -0,0 +1,145 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use std::rc::Rc;
+
+use ranno::anno::Cardinality;
+use ranno::compound::{ChildMut, CompoundMut};
+use ranno::walk::Nth;
+use ranno::{Annotated, Annotation, Branch, BranchMut, Compound};
+
+#[derive(Debug, PartialEq)]
+struct Leaf(u64);
+
+impl Annotation<Leaf> for Cardinality {
+    fn from_child(_: &Leaf) -> Self {
+        Cardinality::LEAF
+    }
+}
+
+fn leaves<C: Compound<Cardinality, Leaf = Leaf>>(root: &C) -> Vec<u64> {
+    root.iter().map(|leaf| leaf.0).collect()
+}
+
+#[ranno::annotation(Cardinality)]
+#[derive(Compound, CompoundMut)]
+enum Tree {
+    Empty,
+    Leaf(#[leaf] Leaf),
+    Pair(
+        Annotated<Box<Tree>, Cardinality>,
+        #[leaf] Leaf,
+        &'static str,
+    ),
+    Node {
+        #[child]
+        children: Vec<Annotated<Box<Tree>, Cardinality>>,
+        label: &'static str,
+    },
+}
+
+fn tree() -> Tree {
+    let leaf = |n| Annotated::new(Box::new(Tree::Leaf(Leaf(n))));
+    Tree::Node {
+        children: vec![
+            Annotated::new(Box::new(Tree::Pair(leaf(1), Leaf(2), "pair"))),
+            Annotated::new(Box::new(Tree::Empty)),
+            Annotated::new(Box::new(Tree::Node {
+                children: vec![leaf(3), leaf(4)],
+                label: "inner",
+            })),
+        ],
+        label: "root",
+    }
+}
+
+impl Tree {
+    fn label(&self) -> Option<&'static str> {
+        match self {
+            Tree::Pair(_, _, label) | Tree::Node { label, .. } => Some(label),
+            _ => None,
+        }
+    }
+}
+
+#[test]
+fn enum_children_are_in_field_order() {
+    let tree = tree();
+    assert_eq!(tree.label(), Some("root"));
+    assert_eq!(tree.children().count(), 3);
+    assert_eq!(Tree::Empty.children().count(), 0);
+    assert_eq!(leaves(&tree), [1, 2, 3, 4]);
+    assert_eq!(Cardinality::from_child(&tree), Cardinality(4));
+
+    let branch = Branch::walk(&tree, Nth::new(2)).unwrap();
+    assert_eq!(*branch, Leaf(3));
+    assert_eq!(branch.path().collect::<Vec<_>>(), [2, 0, 0]);
+}
+
+#[test]
+fn enum_children_can_be_mutated() {
+    let mut tree = tree();
+
+    let mut branch = BranchMut::walk(&mut tree, Nth::new(1)).unwrap();
+    *branch = Leaf(7);
+    drop(branch);
+    assert_eq!(leaves(&tree), [1, 7, 3, 4]);
+
+    match tree.child_mut(1) {
+        Some(ChildMut::Node(node)) => **node.child_mut() = Tree::Leaf(Leaf(5)),
+        _ => panic!("the second child should be a node"),
+    }
+    assert_eq!(leaves(&tree), [1, 7, 5, 3, 4]);
+    assert_eq!(Cardinality::from_child(&tree), Cardinality(5));
+    assert!(tree.child_mut(3).is_none());
+}
+
+#[derive(Compound)]
+#[compound(leaf = Leaf)]
+struct Branches(#[child] Vec<Annotated<Branches, Cardinality>>);
+
+impl Annotation<Branches> for Cardinality {
+    fn from_child(branches: &Branches) -> Self {
+        Cardinality(branches.0.iter().map(|b| b.anno().0).sum())
+    }
+}
+
+#[test]
+fn leaf_type_can_be_given() {
+    let branches = Branches(vec![
+        Annotated::new(Branches(vec![])),
+        Annotated::new(Branches(vec![Annotated::new(Branches(vec![]))])),
+    ]);
+
+    assert_eq!(branches.children().count(), 2);
+    assert!(branches.children().all(|child| child.is_node()));
+    assert!(leaves(&branches).is_empty());
+}
+
+#[derive(Compound)]
+enum List<T> {
+    Nil,
+    Cons(#[leaf] T, Annotated<Rc<List<T>>, Cardinality>),
+}
+
+impl<T> Annotation<List<T>> for Cardinality {
+    fn from_child(list: &List<T>) -> Self {
+        match list {
+            List::Nil => Cardinality(0),
+            List::Cons(_, next) => Cardinality(1 + next.anno().0),
+        }
+    }
+}
+
+#[test]
+fn generic_structures_can_be_derived() {
+    let list = [3, 2, 1].into_iter().fold(List::Nil, |list, n| {
+        List::Cons(Leaf(n), Annotated::new(Rc::new(list)))
+    });
+
+    assert_eq!(list.children().count(), 2);
+    assert_eq!(leaves(&list), [1, 2, 3]);
+}
@@ -0,0 +1,62 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use ranno::anno::{Cardinality, Sum};
+use ranno::{Annotated, Annotation, AnnotationDelegate};
+
+struct Node(Vec<u64>);
+
+impl Annotation<Node> for Cardinality {
+    fn from_child(node: &Node) -> Self {
+        Cardinality(node.0.len() as u64)
+    }
+}
+
+impl Annotation<Node> for Sum<u64> {
+    fn from_child(node: &Node) -> Self {
+        Sum(node.0.iter().sum())
+    }
+}
+
+#[derive(AnnotationDelegate)]
+#[delegate(Cardinality)]
+#[delegate(Sum<u64>)]
+struct Page {
+    number: u32,
+    #[delegate]
+    node: Node,
+}
+
+#[test]
+fn marked_field_is_delegated_to() {
+    let page = Page {
+        number: 1,
+        node: Node(vec![1, 2, 3]),
+    };
+    assert_eq!(page.number, 1);
+
+    assert_eq!(Cardinality::from_child(&page), Cardinality(3));
+    assert_eq!(Sum::from_child(&page), Sum(6));
+
+    let mut page = Annotated::<_, Sum<u64>>::new(page);
+    assert_eq!(*page.anno(), Sum(6));
+    page.child_mut().node.0.push(4);
+    assert_eq!(*page.anno(), Sum(10));
+}
+
+#[derive(AnnotationDelegate)]
+#[delegate(Cardinality, Sum<u64>)]
+struct Wrapper<T>(T);
+
+#[test]
+fn generic_wrapper_delegates_to_its_parameter() {
+    let wrapper = Wrapper(Node(vec![4, 5]));
+    assert_eq!(Cardinality::from_child(&wrapper), Cardinality(2));
+    assert_eq!(Sum::from_child(&wrapper), Sum(9));
+
+    let nested = Wrapper(wrapper);
+    assert_eq!(Sum::from_child(&nested), Sum(9));
+}
@@ -0,0 +1,37 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use ranno::anno::{Keyed, MaxKey, MinKey};
+
+#[derive(Keyed)]
+struct Pair(&'static str, #[key] u32);
+
+#[test]
+fn tuple_struct_is_keyed_by_marked_field() {
+    let pair = Pair("value", 42);
+    assert_eq!(pair.0, "value");
+    assert_eq!(pair.key(), &42);
+
+    assert_eq!(MaxKey::from_leaf(&pair), MaxKey::Maximum(42));
+    assert_eq!(MinKey::from_leaf(&pair), MinKey::Minimum(42));
+}
+
+#[derive(Keyed)]
+struct Entry<K, V> {
+    value: V,
+    #[key]
+    key: K,
+}
+
+#[test]
+fn generic_struct_is_keyed_by_its_parameter() {
+    let entry = Entry {
+        value: 7,
+        key: String::from("key"),
+    };
+    assert_eq!(entry.value, 7);
+    assert_eq!(Keyed::<String>::key(&entry), "key");
+}
@@ -0,0 +1,98 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use ranno::anno::{Cardinality, Keyed, MaxKey, Sum};
+use ranno::{Annotated, Annotation};
+
+struct Leaf(u64);
+
+impl Annotation<Leaf> for Cardinality {
+    fn from_child(_: &Leaf) -> Self {
+        Cardinality::LEAF
+    }
+}
+
+impl Annotation<Leaf> for Sum<u64> {
+    fn from_child(leaf: &Leaf) -> Self {
+        Sum(leaf.0)
+    }
+}
+
+#[ranno::annotation(Cardinality)]
+#[ranno::annotation(Sum<u64>)]
+struct Node(#[leaf] Leaf, #[child] Vec<Node>);
+
+#[test]
+fn stacked_annotations_over_tuple_struct() {
+    let node = Node(
+        Leaf(1),
+        vec![
+            Node(Leaf(2), vec![]),
+            Node(Leaf(3), vec![Node(Leaf(4), vec![])]),
+        ],
+    );
+
+    assert_eq!(Cardinality::from_child(&node), Cardinality(4));
+    assert_eq!(Sum::from_child(&node), Sum(10));
+}
+
+#[derive(Keyed)]
+struct Entry(#[key] u32);
+
+#[ranno::annotation(
+    MaxKey<u32>,
+    for = Tree<MaxKey<u32>>,
+    leaf = MaxKey::from_leaf
+)]
+enum Tree<A> {
+    Empty,
+    Leaf(#[leaf] Entry, &'static str),
+    Node {
+        #[child]
+        children: [Annotated<Box<Tree<A>>, A>; 2],
+        depth: u8,
+    },
+}
+
+impl<A> Tree<A> {
+    fn name(&self) -> Option<&'static str> {
+        match self {
+            Tree::Leaf(_, name) => Some(name),
+            _ => None,
+        }
+    }
+
+    fn depth(&self) -> u8 {
+        match self {
+            Tree::Node { depth, .. } => *depth,
+            _ => 0,
+        }
+    }
+}
+
+#[test]
+fn generic_enum_with_leaf_function() {
+    let leaf = |key| Annotated::new(Box::new(Tree::Leaf(Entry(key), "leaf")));
+    let tree = Tree::Node {
+        children: [
+            Annotated::new(Box::new(Tree::Node {
+                children: [leaf(5), leaf(2)],
+                depth: 1,
+            })),
+            Annotated::new(Box::new(Tree::Empty)),
+        ],
+        depth: 0,
+    };
+
+    assert_eq!(tree.depth(), 0);
+    assert_eq!(tree.name(), None);
+    assert_eq!(MaxKey::from_child(&tree), MaxKey::Maximum(5));
+    assert_eq!(MaxKey::from_child(&Tree::Empty), MaxKey::NegativeInfinity);
+
+    let leaf = Tree::Leaf(Entry(3), "leaf");
+    assert_eq!(leaf.name(), Some("leaf"));
+    assert_eq!(MaxKey::from_child(&leaf), MaxKey::Maximum(3));
+}
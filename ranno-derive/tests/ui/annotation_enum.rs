@@ -0,0 +1,11 @@
+use ranno::anno::Cardinality;
+
+struct Leaf;
+
+#[derive(ranno::Annotation)]
+#[annotation(child = Leaf)]
+enum Anno {
+    Count(Cardinality),
+}
+
+fn main() {}
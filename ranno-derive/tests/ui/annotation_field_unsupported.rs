@@ -0,0 +1,12 @@
+use ranno::anno::Cardinality;
+
+struct Leaf;
+
+#[derive(ranno::Annotation)]
+#[annotation(child = Leaf)]
+struct Anno {
+    #[annotation(borrow)]
+    count: Cardinality,
+}
+
+fn main() {}
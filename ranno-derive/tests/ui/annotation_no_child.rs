@@ -0,0 +1,8 @@
+use ranno::anno::Cardinality;
+
+#[derive(ranno::Annotation)]
+struct Anno {
+    count: Cardinality,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use ranno::anno::Cardinality;
+
+struct Leaf;
+
+#[derive(ranno::Annotation)]
+#[annotation(child = Leaf, parent = Leaf)]
+struct Anno {
+    count: Cardinality,
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use ranno::anno::Cardinality;
+
+#[derive(ranno::Combine)]
+enum Anno {
+    Count(Cardinality),
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use ranno::anno::Cardinality;
+
+#[derive(ranno::Combine)]
+struct Anno {
+    count: Cardinality,
+    #[combine(min)]
+    smallest: u64,
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+#[derive(ranno::Compound)]
+enum Tree {
+    Leaf(#[leaf] u64),
+    Node(Vec<Tree>),
+}
+
+fn main() {}
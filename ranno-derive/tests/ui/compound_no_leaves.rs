@@ -0,0 +1,7 @@
+use ranno::anno::Cardinality;
+use ranno::Annotated;
+
+#[derive(ranno::Compound)]
+struct Tree(#[child] Vec<Annotated<Tree, Cardinality>>);
+
+fn main() {}
@@ -0,0 +1,6 @@
+#[derive(ranno::Compound)]
+union Tree {
+    leaf: u64,
+}
+
+fn main() {}
@@ -0,0 +1,4 @@
+#[derive(ranno::AnnotationDelegate)]
+struct Wrapper(u64);
+
+fn main() {}
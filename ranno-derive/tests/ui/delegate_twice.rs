@@ -0,0 +1,10 @@
+#[derive(ranno::AnnotationDelegate)]
+#[delegate(ranno::anno::Cardinality)]
+struct Wrapper {
+    #[delegate]
+    first: u64,
+    #[delegate]
+    second: u64,
+}
+
+fn main() {}
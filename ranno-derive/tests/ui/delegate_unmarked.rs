@@ -0,0 +1,8 @@
+#[derive(ranno::AnnotationDelegate)]
+#[delegate(ranno::anno::Cardinality)]
+struct Wrapper {
+    first: u64,
+    second: u64,
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+#[derive(ranno::anno::Keyed)]
+struct Entry {
+    id: u64,
+    value: u64,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+#[derive(ranno::anno::Keyed)]
+struct Entry {
+    #[key]
+    id: u64,
+    #[key]
+    value: u64,
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+#[ranno::annotation(ranno::anno::Cardinality)]
+union Node {
+    leaf: u64,
+}
+
+fn main() {}
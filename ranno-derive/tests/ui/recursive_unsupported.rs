@@ -0,0 +1,4 @@
+#[ranno::annotation(ranno::anno::Cardinality, root = Node)]
+struct Node;
+
+fn main() {}
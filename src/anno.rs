@@ -0,0 +1,74 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Commonly used annotations.
+//!
+//! Most annotations in this module implement [`Combine`], and provide
+//! constructors for computing them over a single leaf. Since they can't know
+//! the shape of the structures they annotate, implementing [`Annotation`] for
+//! them is left to the crate defining the structure, typically by combining
+//! the annotations of each child:
+//!
+//! ```
+//! use ranno::anno::{Keyed, MaxKey};
+//! use ranno::{combine, Annotated, Annotation};
+//!
+//! struct Leaf(u32);
+//!
+//! impl Keyed<u32> for Leaf {
+//!     fn key(&self) -> &u32 {
+//!         &self.0
+//!     }
+//! }
+//!
+//! struct Node(Vec<Annotated<Leaf, MaxKey<u32>>>);
+//!
+//! impl Annotation<Leaf> for MaxKey<u32> {
+//!     fn from_child(leaf: &Leaf) -> Self {
+//!         MaxKey::from_leaf(leaf)
+//!     }
+//! }
+//!
+//! impl Annotation<Node> for MaxKey<u32> {
+//!     fn from_child(node: &Node) -> Self {
+//!         combine::combine_children(&node.0)
+//!     }
+//! }
+//!
+//! let node = Node([3, 7, 5].map(|k| Annotated::new(Leaf(k))).into());
+//! let node = Annotated::<_, MaxKey<u32>>::new(node);
+//!
+//! assert_eq!(*node.anno(), MaxKey::Maximum(7));
+//! ```
+//!
+//! [`Combine`]: crate::Combine
+//! [`Annotation`]: crate::Annotation
+
+mod cardinality;
+mod combined;
+mod extrema;
+mod hashed;
+mod height;
+mod keyed;
+#[cfg(feature = "merkle")]
+mod merkle;
+mod range;
+mod stats;
+mod sum;
+
+pub use cardinality::Cardinality;
+pub use combined::Combined;
+pub use extrema::{Max, Min};
+pub use hashed::Hashed;
+pub use height::Height;
+pub use keyed::{Keyed, MaxKey, MinKey};
+#[cfg(feature = "merkle")]
+pub use merkle::{Merkle, MerkleDigest};
+pub use range::Range;
+#[cfg(feature = "derive")]
+pub use ranno_derive::Keyed;
+pub use stats::Stats;
+pub use sum::Sum;
@@ -0,0 +1,88 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use crate::{Annotation, Combine, IncrementalAnnotation};
+
+/// Annotation keeping track of the number of leaves in a structure.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Cardinality(pub u64);
+
+impl Cardinality {
+    /// The cardinality of a single leaf.
+    pub const LEAF: Self = Self(1);
+
+    /// Compute the annotation over a single leaf.
+    pub fn from_leaf<T: ?Sized>(_: &T) -> Self {
+        Self::LEAF
+    }
+}
+
+impl From<Cardinality> for u64 {
+    fn from(c: Cardinality) -> Self {
+        c.0
+    }
+}
+
+impl Combine for Cardinality {
+    fn identity() -> Self {
+        Self(0)
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        Self(self.0 + other.0)
+    }
+}
+
+impl<C, L> IncrementalAnnotation<C, L> for Cardinality
+where
+    Cardinality: Annotation<C>,
+{
+    fn on_insert(&mut self, _: &L) {
+        self.0 += 1;
+    }
+
+    fn on_remove(&mut self, _: &L) {
+        // removing from an empty structure leaves it empty
+        self.0 = self.0.saturating_sub(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Node;
+
+    impl Annotation<Node> for Cardinality {
+        fn from_child(_: &Node) -> Self {
+            Cardinality(0)
+        }
+    }
+
+    fn insert(c: &mut Cardinality) {
+        IncrementalAnnotation::<Node, u64>::on_insert(c, &0);
+    }
+
+    fn remove(c: &mut Cardinality) {
+        IncrementalAnnotation::<Node, u64>::on_remove(c, &0);
+    }
+
+    #[test]
+    fn insert_and_remove_count_leaves() {
+        let mut c = Cardinality::identity();
+        insert(&mut c);
+        insert(&mut c);
+        remove(&mut c);
+        assert_eq!(c, Cardinality(1));
+    }
+
+    #[test]
+    fn remove_from_empty_does_not_underflow() {
+        let mut c = Cardinality::identity();
+        remove(&mut c);
+        assert_eq!(c, Cardinality(0));
+    }
+}
@@ -0,0 +1,114 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use crate::component::{AnnoComponent, Left, Right};
+use crate::{Annotation, Combine};
+
+/// Annotation combining two independent annotations.
+///
+/// Each annotation can be accessed directly, or through [`AnnoComponent`]
+/// by code that only knows the type of the component it is interested in.
+/// Combinations of more than two annotations can be built by nesting.
+///
+/// The components are exposed through [`AnnoComponent`] indexed by [`Left`]
+/// and [`Right`], rather than by implementing `Borrow<A>` and `Borrow<B>`.
+/// The two `Borrow` implementations would overlap when `A` and `B` are the
+/// same type, which coherence rules out, while the indices keep them apart
+/// and are usually inferred.
+///
+/// As with tuples, [`Annotation`] should be implemented over the child in
+/// question, typically using [`compute`]:
+///
+/// ```
+/// use ranno::anno::{Cardinality, Combined, Sum};
+/// use ranno::component;
+/// use ranno::Annotation;
+///
+/// struct Leaf(u64);
+///
+/// impl Annotation<Leaf> for Cardinality {
+///     fn from_child(_: &Leaf) -> Self {
+///         Cardinality::LEAF
+///     }
+/// }
+///
+/// impl Annotation<Leaf> for Sum<u64> {
+///     fn from_child(leaf: &Leaf) -> Self {
+///         Sum(leaf.0)
+///     }
+/// }
+///
+/// impl Annotation<Leaf> for Combined<Cardinality, Sum<u64>> {
+///     fn from_child(leaf: &Leaf) -> Self {
+///         Combined::compute(leaf)
+///     }
+/// }
+///
+/// let anno = Combined::<Cardinality, Sum<u64>>::from_child(&Leaf(42));
+/// let sum: &Sum<u64> = component::component(&anno);
+///
+/// assert_eq!(*sum, Sum(42));
+/// ```
+///
+/// [`compute`]: Combined::compute
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Combined<A, B> {
+    /// The first annotation.
+    pub left: A,
+    /// The second annotation.
+    pub right: B,
+}
+
+impl<A, B> Combined<A, B> {
+    /// Combine the two given annotations.
+    pub const fn new(left: A, right: B) -> Self {
+        Self { left, right }
+    }
+
+    /// Compute both annotations over the given child.
+    pub fn compute<C>(child: &C) -> Self
+    where
+        A: Annotation<C>,
+        B: Annotation<C>,
+    {
+        Self::new(A::from_child(child), B::from_child(child))
+    }
+}
+
+impl<A, B> Combine for Combined<A, B>
+where
+    A: Combine,
+    B: Combine,
+{
+    fn identity() -> Self {
+        Self::new(A::identity(), B::identity())
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        Self::new(
+            self.left.combine(&other.left),
+            self.right.combine(&other.right),
+        )
+    }
+}
+
+impl<T, I, A, B> AnnoComponent<T, Left<I>> for Combined<A, B>
+where
+    A: AnnoComponent<T, I>,
+{
+    fn component(&self) -> &T {
+        self.left.component()
+    }
+}
+
+impl<T, I, A, B> AnnoComponent<T, Right<I>> for Combined<A, B>
+where
+    B: AnnoComponent<T, I>,
+{
+    fn component(&self) -> &T {
+        self.right.component()
+    }
+}
@@ -0,0 +1,142 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use crate::Combine;
+
+/// Annotation keeping track of the maximum leaf in a structure.
+///
+/// The annotation is empty for structures with no leaves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Max<T>(pub Option<T>);
+
+impl<T> Max<T> {
+    /// Compute the annotation over a single leaf.
+    pub fn from_leaf(leaf: &T) -> Self
+    where
+        T: Clone,
+    {
+        Self(Some(leaf.clone()))
+    }
+
+    /// Returns the maximum leaf, if any.
+    pub fn get(&self) -> Option<&T> {
+        self.0.as_ref()
+    }
+}
+
+impl<T> Default for Max<T> {
+    fn default() -> Self {
+        Self(None)
+    }
+}
+
+impl<T> Combine for Max<T>
+where
+    T: Clone + Ord,
+{
+    fn identity() -> Self {
+        Self(None)
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        // `None` is smaller than any leaf
+        Ord::max(self, other).clone()
+    }
+}
+
+/// Annotation keeping track of the minimum leaf in a structure.
+///
+/// The annotation is empty for structures with no leaves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Min<T>(pub Option<T>);
+
+impl<T> Min<T> {
+    /// Compute the annotation over a single leaf.
+    pub fn from_leaf(leaf: &T) -> Self
+    where
+        T: Clone,
+    {
+        Self(Some(leaf.clone()))
+    }
+
+    /// Returns the minimum leaf, if any.
+    pub fn get(&self) -> Option<&T> {
+        self.0.as_ref()
+    }
+}
+
+impl<T> Default for Min<T> {
+    fn default() -> Self {
+        Self(None)
+    }
+}
+
+impl<T> Combine for Min<T>
+where
+    T: Clone + Ord,
+{
+    fn identity() -> Self {
+        Self(None)
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        match (&self.0, &other.0) {
+            (Some(a), Some(b)) => Self(Some(Ord::min(a, b).clone())),
+            (Some(_), None) => self.clone(),
+            (None, _) => other.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn max(leaves: &[i32]) -> Max<i32> {
+        leaves
+            .iter()
+            .map(Max::from_leaf)
+            .fold(Max::identity(), |acc, m| acc.combine(&m))
+    }
+
+    fn min(leaves: &[i32]) -> Min<i32> {
+        leaves
+            .iter()
+            .map(Min::from_leaf)
+            .fold(Min::identity(), |acc, m| acc.combine(&m))
+    }
+
+    #[test]
+    fn no_children_have_no_extrema() {
+        assert_eq!(max(&[]).get(), None);
+        assert_eq!(min(&[]).get(), None);
+        assert_eq!(Max::<i32>::default(), Max::identity());
+        assert_eq!(Min::<i32>::default(), Min::identity());
+    }
+
+    #[test]
+    fn extrema_of_leaves() {
+        assert_eq!(max(&[3, -7, 5, 0]).get(), Some(&5));
+        assert_eq!(min(&[3, -7, 5, 0]).get(), Some(&-7));
+
+        assert_eq!(max(&[-4]).get(), Some(&-4));
+        assert_eq!(min(&[-4]).get(), Some(&-4));
+
+        assert_eq!(max(&[i32::MIN, i32::MIN]).get(), Some(&i32::MIN));
+        assert_eq!(min(&[i32::MAX, i32::MAX]).get(), Some(&i32::MAX));
+    }
+
+    #[test]
+    fn identity_is_neutral() {
+        let a = Max::from_leaf(&-1);
+        assert_eq!(a.combine(&Max::identity()), a);
+        assert_eq!(Max::identity().combine(&a), a);
+
+        let a = Min::from_leaf(&1);
+        assert_eq!(a.combine(&Min::identity()), a);
+        assert_eq!(Min::identity().combine(&a), a);
+    }
+}
@@ -0,0 +1,124 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::marker::PhantomData;
+
+/// Annotation keeping a structural hash of a child, computed using the hasher
+/// `H`.
+///
+/// Hashing a whole structure is as expensive as traversing it. To cheaply
+/// detect changed subtrees, the annotation over a node should be computed
+/// from the annotations over its children using [`of_children`], instead of
+/// hashing the children themselves.
+///
+/// [`of_children`]: Hashed::of_children
+pub struct Hashed<H> {
+    digest: u64,
+    _marker: PhantomData<fn() -> H>,
+}
+
+impl<H> Hashed<H>
+where
+    H: Hasher + Default,
+{
+    /// Compute the annotation by hashing the given value.
+    pub fn of<T>(value: &T) -> Self
+    where
+        T: ?Sized + Hash,
+    {
+        let mut hasher = H::default();
+        value.hash(&mut hasher);
+        Self::from_digest(hasher.finish())
+    }
+
+    /// Compute the annotation by hashing the digests of the given
+    /// annotations, in order.
+    pub fn of_children<'a, I>(children: I) -> Self
+    where
+        H: 'a,
+        I: IntoIterator<Item = &'a Self>,
+    {
+        let mut hasher = H::default();
+        for child in children {
+            hasher.write_u64(child.digest);
+        }
+        Self::from_digest(hasher.finish())
+    }
+}
+
+impl<H> Hashed<H> {
+    /// Create the annotation from an already computed digest.
+    pub const fn from_digest(digest: u64) -> Self {
+        Self {
+            digest,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the digest of the child.
+    pub const fn digest(&self) -> u64 {
+        self.digest
+    }
+}
+
+impl<H> fmt::Debug for Hashed<H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Hashed").field(&self.digest).finish()
+    }
+}
+
+impl<H> Clone for Hashed<H> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<H> Copy for Hashed<H> {}
+
+impl<H> PartialEq for Hashed<H> {
+    fn eq(&self, other: &Self) -> bool {
+        self.digest == other.digest
+    }
+}
+
+impl<H> Eq for Hashed<H> {}
+
+impl<H> Hash for Hashed<H> {
+    fn hash<S: Hasher>(&self, state: &mut S) {
+        self.digest.hash(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::collections::hash_map::DefaultHasher;
+
+    use super::*;
+
+    type H = Hashed<DefaultHasher>;
+
+    #[test]
+    fn equal_values_have_equal_digests() {
+        assert_eq!(H::of("leaf"), H::of("leaf"));
+        assert_ne!(H::of("leaf"), H::of("other"));
+        assert_eq!(H::from_digest(H::of(&42).digest()), H::of(&42));
+    }
+
+    #[test]
+    fn node_digest_depends_on_children_in_order() {
+        let (a, b, c) = (H::of(&1), H::of(&2), H::of(&3));
+
+        let node = H::of_children(&[a, b]);
+        assert_eq!(node, H::of_children(&[a, b]));
+        assert_ne!(node, H::of_children(&[b, a]));
+        assert_ne!(node, H::of_children(&[a, c]));
+        assert_ne!(node, H::of_children(&[a]));
+    }
+}
@@ -0,0 +1,87 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use crate::Combine;
+
+/// Annotation keeping track of the height of a structure, i.e. the number of
+/// levels in its longest path from the root to a leaf.
+///
+/// Combining heights yields the largest of them, and the height of a node is
+/// computed from the heights of its children using [`from_children`].
+///
+/// Unlike the height, the depth of a node depends on its ancestors rather
+/// than its children, and can't be cached as an annotation.
+///
+/// [`from_children`]: Height::from_children
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Height(pub u32);
+
+impl Height {
+    /// The height of an empty structure.
+    pub const EMPTY: Self = Self(0);
+
+    /// The height of a single leaf.
+    pub const LEAF: Self = Self(1);
+
+    /// Compute the height of a node from the heights of its children.
+    pub fn from_children<'a, I>(children: I) -> Self
+    where
+        I: IntoIterator<Item = &'a Self>,
+    {
+        let height = crate::combine::combine_all(children);
+        Self(height.0 + 1)
+    }
+
+    /// Returns the difference in height between two subtrees, as used to
+    /// balance AVL trees.
+    pub fn balance(left: &Self, right: &Self) -> i64 {
+        i64::from(left.0) - i64::from(right.0)
+    }
+}
+
+impl Combine for Height {
+    fn identity() -> Self {
+        Self::EMPTY
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        Ord::max(*self, *other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn node_without_children_has_height_one() {
+        assert_eq!(Height::from_children([]), Height(1));
+        assert_eq!(Height::from_children([&Height::EMPTY]), Height(1));
+    }
+
+    #[test]
+    fn nesting_increases_height() {
+        let leaf = Height::LEAF;
+        let node = Height::from_children([&leaf, &leaf]);
+        assert_eq!(node, Height(2));
+
+        let root = Height::from_children([&leaf, &node]);
+        assert_eq!(root, Height(3));
+
+        let root = Height::from_children([&root]);
+        assert_eq!(root, Height(4));
+    }
+
+    #[test]
+    fn balance_is_difference_in_height() {
+        assert_eq!(Height::balance(&Height(3), &Height(1)), 2);
+        assert_eq!(Height::balance(&Height::EMPTY, &Height(2)), -2);
+        assert_eq!(
+            Height::balance(&Height(u32::MAX), &Height::EMPTY),
+            4294967295
+        );
+    }
+}
@@ -0,0 +1,97 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use crate::Combine;
+
+/// A leaf identified by a key.
+pub trait Keyed<K> {
+    /// Returns the key of the leaf.
+    fn key(&self) -> &K;
+}
+
+/// Annotation keeping track of the maximum key in a structure.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MaxKey<K> {
+    /// Identity of the annotation, smaller than any key.
+    #[default]
+    NegativeInfinity,
+    /// Actual maximum key.
+    Maximum(K),
+}
+
+impl<K> MaxKey<K> {
+    /// Compute the annotation over a single leaf.
+    pub fn from_leaf<L>(leaf: &L) -> Self
+    where
+        K: Clone,
+        L: Keyed<K>,
+    {
+        Self::Maximum(leaf.key().clone())
+    }
+
+    /// Returns the maximum key, if any.
+    pub fn key(&self) -> Option<&K> {
+        match self {
+            Self::NegativeInfinity => None,
+            Self::Maximum(key) => Some(key),
+        }
+    }
+}
+
+impl<K> Combine for MaxKey<K>
+where
+    K: Clone + Ord,
+{
+    fn identity() -> Self {
+        Self::NegativeInfinity
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        Ord::max(self, other).clone()
+    }
+}
+
+/// Annotation keeping track of the minimum key in a structure.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MinKey<K> {
+    /// Actual minimum key.
+    Minimum(K),
+    /// Identity of the annotation, larger than any key.
+    #[default]
+    PositiveInfinity,
+}
+
+impl<K> MinKey<K> {
+    /// Compute the annotation over a single leaf.
+    pub fn from_leaf<L>(leaf: &L) -> Self
+    where
+        K: Clone,
+        L: Keyed<K>,
+    {
+        Self::Minimum(leaf.key().clone())
+    }
+
+    /// Returns the minimum key, if any.
+    pub fn key(&self) -> Option<&K> {
+        match self {
+            Self::Minimum(key) => Some(key),
+            Self::PositiveInfinity => None,
+        }
+    }
+}
+
+impl<K> Combine for MinKey<K>
+where
+    K: Clone + Ord,
+{
+    fn identity() -> Self {
+        Self::PositiveInfinity
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        Ord::min(self, other).clone()
+    }
+}
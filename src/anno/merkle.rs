@@ -0,0 +1,217 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use core::fmt;
+use core::hash::{Hash, Hasher};
+
+/// A cryptographic hash function used to compute Merkle commitments.
+///
+/// Implementations are provided for `blake3` and `sha2` hashers, behind the
+/// features of the same name.
+pub trait MerkleDigest: Default {
+    /// The output of the hash function.
+    type Output: AsRef<[u8]> + Clone + Eq;
+
+    /// Feed some bytes to the hasher.
+    fn update(&mut self, bytes: &[u8]);
+
+    /// Consume the hasher, returning the hash of all bytes fed to it.
+    fn finalize(self) -> Self::Output;
+}
+
+// Leaves and nodes are hashed with different prefixes, so that a leaf can
+// never be passed off as a node, or vice-versa.
+const LEAF_PREFIX: u8 = 0;
+const NODE_PREFIX: u8 = 1;
+
+/// Annotation keeping a Merkle commitment to a structure, computed using the
+/// hash function `D`.
+///
+/// The commitment to a node is the hash of the commitments to its children,
+/// in order. Unlike most other annotations, combining commitments is not
+/// associative, and they should be computed from all the children of a node
+/// at once using [`from_children`].
+///
+/// [`from_children`]: Merkle::from_children
+pub struct Merkle<D: MerkleDigest> {
+    hash: D::Output,
+}
+
+impl<D: MerkleDigest> Merkle<D> {
+    /// Compute the commitment to a leaf with the given byte representation.
+    pub fn from_leaf(bytes: &[u8]) -> Self {
+        let mut digest = D::default();
+        digest.update(&[LEAF_PREFIX]);
+        digest.update(bytes);
+        Self::from_hash(digest.finalize())
+    }
+
+    /// Compute the commitment to a node from the commitments to its
+    /// children, in order.
+    pub fn from_children<'a, I>(children: I) -> Self
+    where
+        D: 'a,
+        I: IntoIterator<Item = &'a Self>,
+    {
+        let mut digest = D::default();
+        digest.update(&[NODE_PREFIX]);
+        for child in children {
+            digest.update(child.hash.as_ref());
+        }
+        Self::from_hash(digest.finalize())
+    }
+
+    /// Create the annotation from an already computed commitment.
+    pub fn from_hash(hash: D::Output) -> Self {
+        Self { hash }
+    }
+
+    /// Returns the commitment to the structure.
+    pub fn hash(&self) -> &D::Output {
+        &self.hash
+    }
+}
+
+impl<D: MerkleDigest> fmt::Debug for Merkle<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Merkle(")?;
+        for byte in self.hash.as_ref() {
+            write!(f, "{byte:02x}")?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl<D: MerkleDigest> Clone for Merkle<D> {
+    fn clone(&self) -> Self {
+        Self::from_hash(self.hash.clone())
+    }
+}
+
+impl<D: MerkleDigest> PartialEq for Merkle<D> {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash
+    }
+}
+
+impl<D: MerkleDigest> Eq for Merkle<D> {}
+
+impl<D: MerkleDigest> Hash for Merkle<D> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.hash.as_ref().hash(state)
+    }
+}
+
+#[cfg(feature = "blake3")]
+impl MerkleDigest for blake3::Hasher {
+    type Output = [u8; 32];
+
+    fn update(&mut self, bytes: &[u8]) {
+        blake3::Hasher::update(self, bytes);
+    }
+
+    fn finalize(self) -> Self::Output {
+        *blake3::Hasher::finalize(&self).as_bytes()
+    }
+}
+
+#[cfg(feature = "sha2")]
+mod impl_sha2 {
+    use super::MerkleDigest;
+
+    use sha2::{Digest, Sha256, Sha512};
+
+    impl MerkleDigest for Sha256 {
+        type Output = [u8; 32];
+
+        fn update(&mut self, bytes: &[u8]) {
+            Digest::update(self, bytes);
+        }
+
+        fn finalize(self) -> Self::Output {
+            Digest::finalize(self).into()
+        }
+    }
+
+    impl MerkleDigest for Sha512 {
+        type Output = [u8; 64];
+
+        fn update(&mut self, bytes: &[u8]) {
+            Digest::update(self, bytes);
+        }
+
+        fn finalize(self) -> Self::Output {
+            Digest::finalize(self).into()
+        }
+    }
+}
+
+#[cfg(all(test, any(feature = "blake3", feature = "sha2")))]
+mod tests {
+    extern crate std;
+
+    use std::format;
+
+    use super::*;
+
+    fn hex<D: MerkleDigest>(merkle: &Merkle<D>) -> std::string::String {
+        let debug = format!("{merkle:?}");
+        debug["Merkle(".len()..debug.len() - 1].into()
+    }
+
+    fn pair<D: MerkleDigest>(a: &[u8], b: &[u8]) -> Merkle<D> {
+        Merkle::from_children(&[Merkle::from_leaf(a), Merkle::from_leaf(b)])
+    }
+
+    #[cfg(feature = "blake3")]
+    #[test]
+    fn blake3_known_answers() {
+        type M = Merkle<blake3::Hasher>;
+
+        // the leaf prefix is the only byte hashed
+        assert_eq!(
+            hex(&M::from_leaf(b"")),
+            "2d3adedff11b61f14c886e35afa036736dcd87a74d27b5c1510225d0f592e213"
+        );
+        assert_ne!(pair::<blake3::Hasher>(b"a", b"b"), pair(b"b", b"a"));
+    }
+
+    #[cfg(feature = "sha2")]
+    #[test]
+    fn sha256_known_answers() {
+        type M = Merkle<sha2::Sha256>;
+
+        assert_eq!(
+            hex(&M::from_leaf(b"")),
+            "6e340b9cffb37a989ca544e6bb780a2c78901d3fb33738768511a30617afa01d"
+        );
+        assert_eq!(
+            hex(&M::from_leaf(b"abc")),
+            "609f6e36d2405585188d5cfd761f407c7cc46a7d3f314c88270469dde315fcd1"
+        );
+        assert_eq!(
+            hex(&pair::<sha2::Sha256>(b"a", b"b")),
+            "b137985ff484fb600db93107c77b0365c80d78f5b429ded0fd97361d077999eb"
+        );
+        assert_ne!(pair::<sha2::Sha256>(b"a", b"b"), pair(b"b", b"a"));
+
+        // an empty node isn't confused with an empty leaf
+        assert_ne!(M::from_children([]), M::from_leaf(b""));
+    }
+
+    #[cfg(feature = "sha2")]
+    #[test]
+    fn sha512_known_answers() {
+        type M = Merkle<sha2::Sha512>;
+
+        assert_eq!(
+            hex(&M::from_leaf(b"abc")),
+            "e8bed4e1a44c9946bbdbf11761ee919f9639a7598437f3959357936a37892774\
+             378355cff365b9930588325ed0c0e80a3a576acb677c77d79ab209d9e3a87199"
+        );
+        assert_ne!(pair::<sha2::Sha512>(b"a", b"b"), pair(b"b", b"a"));
+    }
+}
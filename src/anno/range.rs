@@ -0,0 +1,141 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use crate::Combine;
+
+/// Annotation keeping track of the minimum and maximum leaves in a
+/// structure.
+///
+/// The annotation is empty for structures with no leaves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Range<T>(pub Option<(T, T)>);
+
+impl<T> Range<T> {
+    /// Compute the annotation over a single leaf.
+    pub fn from_leaf(leaf: &T) -> Self
+    where
+        T: Clone,
+    {
+        Self(Some((leaf.clone(), leaf.clone())))
+    }
+
+    /// Returns the minimum leaf, if any.
+    pub fn min(&self) -> Option<&T> {
+        self.0.as_ref().map(|(min, _)| min)
+    }
+
+    /// Returns the maximum leaf, if any.
+    pub fn max(&self) -> Option<&T> {
+        self.0.as_ref().map(|(_, max)| max)
+    }
+
+    /// Returns true if the given value lies within the range, inclusively.
+    pub fn contains(&self, value: &T) -> bool
+    where
+        T: Ord,
+    {
+        match &self.0 {
+            Some((min, max)) => min <= value && value <= max,
+            None => false,
+        }
+    }
+
+    /// Returns true if the range intersects the given inclusive interval.
+    ///
+    /// An interval whose `start` is greater than its `end` is empty, and
+    /// overlaps nothing.
+    pub fn overlaps(&self, start: &T, end: &T) -> bool
+    where
+        T: Ord,
+    {
+        match &self.0 {
+            Some((min, max)) => start <= end && min <= end && start <= max,
+            None => false,
+        }
+    }
+}
+
+impl<T> Default for Range<T> {
+    fn default() -> Self {
+        Self(None)
+    }
+}
+
+impl<T> Combine for Range<T>
+where
+    T: Clone + Ord,
+{
+    fn identity() -> Self {
+        Self(None)
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        match (&self.0, &other.0) {
+            (Some((min1, max1)), Some((min2, max2))) => Self(Some((
+                Ord::min(min1, min2).clone(),
+                Ord::max(max1, max2).clone(),
+            ))),
+            (Some(_), None) => self.clone(),
+            (None, _) => other.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(leaves: &[i32]) -> Range<i32> {
+        leaves
+            .iter()
+            .map(Range::from_leaf)
+            .fold(Range::identity(), |acc, r| acc.combine(&r))
+    }
+
+    #[test]
+    fn range_of_leaves() {
+        let r = range(&[4, -2, 9, 0]);
+        assert_eq!(r.min(), Some(&-2));
+        assert_eq!(r.max(), Some(&9));
+
+        let r = range(&[]);
+        assert_eq!(r.min(), None);
+        assert_eq!(r.max(), None);
+    }
+
+    #[test]
+    fn contains_is_inclusive() {
+        let r = range(&[-2, 9]);
+        assert!(r.contains(&-2));
+        assert!(r.contains(&3));
+        assert!(r.contains(&9));
+        assert!(!r.contains(&-3));
+        assert!(!r.contains(&10));
+
+        let r = range(&[5]);
+        assert!(r.contains(&5));
+        assert!(!r.contains(&4));
+        assert!(!r.contains(&6));
+
+        assert!(!range(&[]).contains(&0));
+    }
+
+    #[test]
+    fn overlaps_is_inclusive() {
+        let r = range(&[-2, 9]);
+        assert!(r.overlaps(&0, &3));
+        assert!(r.overlaps(&-10, &20));
+        assert!(r.overlaps(&-5, &-2));
+        assert!(r.overlaps(&9, &12));
+        assert!(!r.overlaps(&-5, &-3));
+        assert!(!r.overlaps(&10, &12));
+
+        // an empty interval overlaps nothing
+        assert!(!r.overlaps(&3, &0));
+
+        assert!(!range(&[]).overlaps(&i32::MIN, &i32::MAX));
+    }
+}
@@ -0,0 +1,170 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use crate::Combine;
+
+/// Annotation keeping running statistics over the leaves of a structure.
+///
+/// The statistics are kept using integer arithmetic, so combining them is
+/// exact, and only computing the [`mean`] and [`variance`] rounds.
+///
+/// # Panics
+/// Combining panics if the sum of the squares of the leaves overflows a
+/// `u128`. This can't happen for less than `2^64` leaves whose magnitude is
+/// at most `2^32`, but can for as little as four leaves whose magnitude is
+/// close to `2^63`.
+///
+/// [`mean`]: Stats::mean
+/// [`variance`]: Stats::variance
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Stats {
+    /// Number of leaves.
+    pub count: u64,
+    /// Sum of the leaves.
+    pub sum: i128,
+    /// Sum of the squares of the leaves.
+    pub sum_squares: u128,
+}
+
+impl Stats {
+    /// Compute the annotation over a single leaf.
+    pub fn from_leaf(leaf: &i64) -> Self {
+        let leaf = i128::from(*leaf);
+        Self {
+            count: 1,
+            sum: leaf,
+            sum_squares: (leaf * leaf) as u128,
+        }
+    }
+
+    /// Returns the mean of the leaves, if there are any.
+    pub fn mean(&self) -> Option<f64> {
+        (self.count != 0).then(|| self.sum as f64 / self.count as f64)
+    }
+
+    /// Returns the population variance of the leaves, if there are any.
+    pub fn variance(&self) -> Option<f64> {
+        if self.count == 0 {
+            return None;
+        }
+
+        // the variance is computed as `(n * sum_squares - sum^2) / n^2`, with
+        // the numerator computed exactly, avoiding the cancellation incurred
+        // when subtracting the squared mean from the mean of the squares
+        let count = u128::from(self.count);
+        let sum = self.sum.unsigned_abs();
+
+        let (hi, lo) = widening_mul(count, self.sum_squares);
+        let (sum_hi, sum_lo) = widening_mul(sum, sum);
+
+        let (lo, borrow) = lo.overflowing_sub(sum_lo);
+        let numerator = match hi.checked_sub(sum_hi + u128::from(borrow)) {
+            Some(hi) => hi as f64 * TWO_POW_128 + lo as f64,
+            // only possible if the fields are inconsistent with each other
+            None => 0.0,
+        };
+
+        let count = self.count as f64;
+        Some(numerator / (count * count))
+    }
+}
+
+/// `2^128`, the weight of the high half of a 256-bit number.
+const TWO_POW_128: f64 = 340_282_366_920_938_463_463_374_607_431_768_211_456.0;
+
+/// Multiply two numbers, returning the high and low halves of the result.
+fn widening_mul(a: u128, b: u128) -> (u128, u128) {
+    const MASK: u128 = u64::MAX as u128;
+
+    let (a_hi, a_lo) = (a >> 64, a & MASK);
+    let (b_hi, b_lo) = (b >> 64, b & MASK);
+
+    let lo_lo = a_lo * b_lo;
+    let hi_lo = a_hi * b_lo;
+    let lo_hi = a_lo * b_hi;
+    let hi_hi = a_hi * b_hi;
+
+    let mid = (lo_lo >> 64) + (hi_lo & MASK) + (lo_hi & MASK);
+    let lo = (mid << 64) | (lo_lo & MASK);
+    let hi = hi_hi + (hi_lo >> 64) + (lo_hi >> 64) + (mid >> 64);
+
+    (hi, lo)
+}
+
+impl Combine for Stats {
+    fn identity() -> Self {
+        Self::default()
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        Self {
+            count: self.count + other.count,
+            sum: self.sum + other.sum,
+            sum_squares: self
+                .sum_squares
+                .checked_add(other.sum_squares)
+                .expect("sum of squares should not overflow"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(leaves: &[i64]) -> Stats {
+        leaves
+            .iter()
+            .map(Stats::from_leaf)
+            .fold(Stats::identity(), |acc, s| acc.combine(&s))
+    }
+
+    #[test]
+    fn variance_of_small_leaves() {
+        let s = stats(&[2, 4, 4, 4, 5, 5, 7, 9]);
+        assert_eq!(s.mean(), Some(5.0));
+        assert_eq!(s.variance(), Some(4.0));
+
+        assert_eq!(stats(&[]).variance(), None);
+        assert_eq!(stats(&[-3]).variance(), Some(0.0));
+    }
+
+    #[test]
+    fn variance_of_large_leaves_is_exact() {
+        let s = stats(&[i64::MAX, i64::MAX - 2]);
+        assert_eq!(s.variance(), Some(1.0));
+
+        let s = stats(&[i64::MIN, i64::MIN + 2, i64::MIN + 4]);
+        assert_eq!(s.variance(), Some(8.0 / 3.0));
+
+        let s = stats(&[i64::MIN + 1, i64::MIN + 1, i64::MIN + 1]);
+        assert_eq!(s.variance(), Some(0.0));
+    }
+
+    #[test]
+    fn variance_is_never_negative() {
+        let s = Stats {
+            count: 2,
+            sum: 10,
+            sum_squares: 0,
+        };
+        assert_eq!(s.variance(), Some(0.0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn sum_of_squares_overflow_panics() {
+        stats(&[i64::MIN; 4]);
+    }
+
+    #[test]
+    fn widening_mul_matches_narrow_products() {
+        assert_eq!(widening_mul(3, 5), (0, 15));
+        assert_eq!(widening_mul(u128::MAX, 1), (0, u128::MAX));
+        assert_eq!(widening_mul(1 << 64, 1 << 64), (1, 0));
+        assert_eq!(widening_mul(u128::MAX, u128::MAX), (u128::MAX - 1, 1));
+    }
+}
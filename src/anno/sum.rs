@@ -0,0 +1,50 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use core::ops::{Add, Sub};
+
+use crate::{Annotation, Combine, IncrementalAnnotation};
+
+/// Annotation keeping track of the sum of the leaves in a structure.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Sum<T>(pub T);
+
+impl<T> Sum<T> {
+    /// Compute the annotation over a single leaf.
+    pub fn from_leaf(leaf: &T) -> Self
+    where
+        T: Copy,
+    {
+        Self(*leaf)
+    }
+}
+
+impl<T> Combine for Sum<T>
+where
+    T: Add<Output = T> + Default + Copy,
+{
+    fn identity() -> Self {
+        Self(T::default())
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        Self(self.0 + other.0)
+    }
+}
+
+impl<C, T> IncrementalAnnotation<C, T> for Sum<T>
+where
+    Sum<T>: Annotation<C>,
+    T: Add<Output = T> + Sub<Output = T> + Copy,
+{
+    fn on_insert(&mut self, leaf: &T) {
+        self.0 = self.0 + *leaf;
+    }
+
+    fn on_remove(&mut self, leaf: &T) {
+        self.0 = self.0 - *leaf;
+    }
+}
@@ -0,0 +1,451 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use core::future::Future;
+use core::mem;
+
+#[cfg(not(feature = "tokio"))]
+use local::AnnoCell;
+#[cfg(feature = "tokio")]
+use shared::AnnoCell;
+
+/// Annotation over a child, computed asynchronously.
+///
+/// This is useful for annotations whose computation needs to perform IO,
+/// such as fetching parts of the child from disk or over the network.
+pub trait AsyncAnnotation<C>: Sized {
+    /// Compute the annotation from the child.
+    fn from_child(t: &C) -> impl Future<Output = Self>;
+}
+
+/// A child annotated with some metadata, computed asynchronously.
+///
+/// Annotations are lazily evaluated, with computation triggered when they are
+/// awaited for using [`anno`]. Computation is single-flight: when the
+/// annotation is awaited for concurrently, only one computation is performed,
+/// and all awaiting tasks are woken up once it's ready.
+///
+/// With the `tokio` feature, the annotation is backed by a
+/// `tokio::sync::OnceCell`, allowing for the annotated child to be shared
+/// between tasks running on different threads.
+///
+/// [`anno`]: AsyncAnnotated::anno
+#[derive(Debug)]
+pub struct AsyncAnnotated<C, A> {
+    child: C,
+    anno: AnnoCell<A>,
+}
+
+impl<C, A> AsyncAnnotated<C, A> {
+    fn from_parts(child: C, anno: Option<A>) -> Self {
+        Self {
+            child,
+            anno: AnnoCell::new(anno),
+        }
+    }
+
+    /// Returns the annotated child.
+    pub fn child(&self) -> &C {
+        &self.child
+    }
+
+    /// Returns the annotation over the child, if it was already computed.
+    pub fn get_anno(&self) -> Option<&A> {
+        self.anno.get()
+    }
+
+    /// Returns true if the annotation over the child is already computed.
+    pub fn is_computed(&self) -> bool {
+        self.anno.get().is_some()
+    }
+
+    /// Invalidate the annotation, forcing it to be re-computed the next time
+    /// it is asked for.
+    pub fn invalidate(&mut self) {
+        self.anno.take();
+    }
+
+    /// Replace the child with a new one, returning the old child.
+    ///
+    /// The annotation is invalidated.
+    pub fn replace_child(&mut self, child: C) -> C {
+        self.invalidate();
+        mem::replace(&mut self.child, child)
+    }
+
+    /// Consume the structure and return the child and the annotation, if it
+    /// was already computed.
+    pub fn split(self) -> (C, Option<A>) {
+        (self.child, self.anno.into_inner())
+    }
+
+    /// Consume the structure and return the child, discarding the annotation.
+    pub fn into_child(self) -> C {
+        self.child
+    }
+}
+
+impl<C, A> AsyncAnnotated<C, A>
+where
+    A: AsyncAnnotation<C>,
+{
+    /// Create a new annotation over a child.
+    pub fn new(child: C) -> Self {
+        Self::from_parts(child, None)
+    }
+
+    /// Create a new annotated child with an already computed annotation.
+    ///
+    /// The annotation is trusted to be the correct one for the child, and
+    /// will not be recomputed until the child is mutated.
+    pub fn with_anno(child: C, anno: A) -> Self {
+        Self::from_parts(child, Some(anno))
+    }
+
+    /// Returns the annotation over the child.
+    pub async fn anno(&self) -> &A {
+        self.anno.get_or_init(A::from_child(&self.child)).await
+    }
+
+    /// Returns a mutable reference to the annotated child.
+    pub fn child_mut(&mut self) -> AsyncAnnotatedRefMut<'_, C, A> {
+        AsyncAnnotatedRefMut { annotated: self }
+    }
+}
+
+impl<C, A> Clone for AsyncAnnotated<C, A>
+where
+    C: Clone,
+    A: Clone,
+{
+    fn clone(&self) -> Self {
+        // the annotation is cloned as well, to avoid re-computing it
+        Self {
+            child: self.child.clone(),
+            anno: self.anno.clone(),
+        }
+    }
+}
+
+crate::macros::impl_by_child!(AsyncAnnotated<C, A>);
+crate::macros::impl_from_child!(AsyncAnnotated<C, A>, AsyncAnnotation);
+
+crate::macros::child_ref_mut! {
+    /// A mutable reference to an asynchronously annotated child.
+    AsyncAnnotatedRefMut => AsyncAnnotated<C, A>
+}
+
+#[cfg(not(feature = "tokio"))]
+mod local {
+    extern crate alloc;
+
+    use alloc::vec::Vec;
+
+    use core::cell::{Cell, OnceCell, RefCell};
+    use core::future::{poll_fn, Future};
+    use core::task::{Poll, Waker};
+
+    /// Single-flight cell for tasks running on the same thread.
+    #[derive(Debug)]
+    pub struct AnnoCell<A> {
+        anno: OnceCell<A>,
+        computing: Cell<bool>,
+        waiters: RefCell<Vec<Waker>>,
+    }
+
+    impl<A> AnnoCell<A> {
+        pub fn new(anno: Option<A>) -> Self {
+            Self {
+                anno: anno.map(OnceCell::from).unwrap_or_default(),
+                computing: Cell::new(false),
+                waiters: RefCell::new(Vec::new()),
+            }
+        }
+
+        pub fn get(&self) -> Option<&A> {
+            self.anno.get()
+        }
+
+        pub fn take(&mut self) -> Option<A> {
+            self.anno.take()
+        }
+
+        pub fn into_inner(self) -> Option<A> {
+            self.anno.into_inner()
+        }
+
+        pub async fn get_or_init<F>(&self, f: F) -> &A
+        where
+            F: Future<Output = A>,
+        {
+            let mut f = Some(f);
+
+            loop {
+                if let Some(anno) = self.anno.get() {
+                    return anno;
+                }
+
+                if self.computing.get() {
+                    // wait for the task computing the annotation to finish,
+                    // or to be cancelled
+                    poll_fn(|cx| {
+                        if !self.computing.get() {
+                            return Poll::Ready(());
+                        }
+
+                        let mut waiters = self.waiters.borrow_mut();
+                        if !waiters.iter().any(|w| w.will_wake(cx.waker())) {
+                            waiters.push(cx.waker().clone());
+                        }
+
+                        Poll::Pending
+                    })
+                    .await;
+                    continue;
+                }
+
+                self.computing.set(true);
+
+                // if the computation is cancelled or panics, waiting tasks are
+                // woken up and one of them will take over
+                let _done = Done(self);
+
+                // unwrapping is ok since the future is only awaited once, the
+                // loop being exited right after
+                let anno = f.take().unwrap().await;
+
+                // only one task is ever computing, so the cell is still empty
+                return self.anno.get_or_init(|| anno);
+            }
+        }
+    }
+
+    impl<A> Clone for AnnoCell<A>
+    where
+        A: Clone,
+    {
+        fn clone(&self) -> Self {
+            Self::new(self.anno.get().cloned())
+        }
+    }
+
+    struct Done<'a, A>(&'a AnnoCell<A>);
+
+    impl<'a, A> Drop for Done<'a, A> {
+        fn drop(&mut self) {
+            self.0.computing.set(false);
+            for waker in self.0.waiters.take() {
+                waker.wake();
+            }
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+mod shared {
+    use core::future::Future;
+
+    use tokio::sync::OnceCell;
+
+    /// Single-flight cell for tasks running on any thread.
+    #[derive(Debug, Clone)]
+    pub struct AnnoCell<A> {
+        anno: OnceCell<A>,
+    }
+
+    impl<A> AnnoCell<A> {
+        pub fn new(anno: Option<A>) -> Self {
+            Self {
+                anno: OnceCell::new_with(anno),
+            }
+        }
+
+        pub fn get(&self) -> Option<&A> {
+            self.anno.get()
+        }
+
+        pub fn take(&mut self) -> Option<A> {
+            self.anno.take()
+        }
+
+        pub fn into_inner(self) -> Option<A> {
+            self.anno.into_inner()
+        }
+
+        pub async fn get_or_init<F>(&self, f: F) -> &A
+        where
+            F: Future<Output = A>,
+        {
+            self.anno.get_or_init(|| f).await
+        }
+    }
+}
+
+#[cfg(all(test, not(feature = "tokio")))]
+mod tests {
+    extern crate std;
+
+    use core::cell::Cell;
+    use core::future::poll_fn;
+    use core::pin::pin;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use core::task::{Context, Poll, Waker};
+    use std::boxed::Box;
+    use std::sync::Arc;
+    use std::task::Wake;
+
+    use super::*;
+
+    /// A leaf whose annotation is only computed once it is released.
+    struct Leaf {
+        value: u64,
+        released: Cell<bool>,
+        computations: Cell<usize>,
+    }
+
+    impl Leaf {
+        fn new(value: u64) -> Self {
+            Self {
+                value,
+                released: Cell::new(false),
+                computations: Cell::new(0),
+            }
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Value(u64);
+
+    impl AsyncAnnotation<Leaf> for Value {
+        async fn from_child(leaf: &Leaf) -> Self {
+            leaf.computations.set(leaf.computations.get() + 1);
+            poll_fn(|_| match leaf.released.get() {
+                true => Poll::Ready(()),
+                false => Poll::Pending,
+            })
+            .await;
+            Value(leaf.value)
+        }
+    }
+
+    #[derive(Default)]
+    struct CountingWaker(AtomicUsize);
+
+    impl Wake for CountingWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    impl CountingWaker {
+        fn wakes(&self) -> usize {
+            self.0.load(Ordering::SeqCst)
+        }
+    }
+
+    #[test]
+    fn concurrent_awaiters_compute_once() {
+        let annotated = AsyncAnnotated::<_, Value>::new(Leaf::new(42));
+
+        let counter = Arc::new(CountingWaker::default());
+        let waker = Waker::from(counter.clone());
+        let mut cx = Context::from_waker(&waker);
+
+        let mut first = pin!(annotated.anno());
+        let mut second = pin!(annotated.anno());
+
+        assert!(first.as_mut().poll(&mut cx).is_pending());
+        assert!(second.as_mut().poll(&mut cx).is_pending());
+        assert_eq!(annotated.child().computations.get(), 1);
+        assert_eq!(counter.wakes(), 0);
+
+        annotated.child().released.set(true);
+        assert_eq!(first.as_mut().poll(&mut cx), Poll::Ready(&Value(42)));
+
+        // the waiting task is woken up, and finds the annotation computed
+        assert_eq!(counter.wakes(), 1);
+        assert_eq!(second.as_mut().poll(&mut cx), Poll::Ready(&Value(42)));
+        assert_eq!(annotated.child().computations.get(), 1);
+        assert!(annotated.is_computed());
+    }
+
+    #[test]
+    fn dropped_computation_is_taken_over() {
+        let annotated = AsyncAnnotated::<_, Value>::new(Leaf::new(42));
+
+        let counter = Arc::new(CountingWaker::default());
+        let waker = Waker::from(counter.clone());
+        let mut cx = Context::from_waker(&waker);
+
+        let mut first = Box::pin(annotated.anno());
+        let mut second = pin!(annotated.anno());
+
+        assert!(first.as_mut().poll(&mut cx).is_pending());
+        assert!(second.as_mut().poll(&mut cx).is_pending());
+
+        // cancelling the computing task wakes up the waiting one
+        drop(first);
+        assert_eq!(counter.wakes(), 1);
+        assert!(!annotated.is_computed());
+
+        assert!(second.as_mut().poll(&mut cx).is_pending());
+        assert_eq!(annotated.child().computations.get(), 2);
+
+        annotated.child().released.set(true);
+        assert_eq!(second.as_mut().poll(&mut cx), Poll::Ready(&Value(42)));
+        assert_eq!(annotated.child().computations.get(), 2);
+    }
+}
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+    extern crate std;
+
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use core::time::Duration;
+    use std::sync::Arc;
+    use std::vec::Vec;
+
+    use super::*;
+
+    struct Leaf {
+        value: u64,
+        computations: AtomicUsize,
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Value(u64);
+
+    impl AsyncAnnotation<Leaf> for Value {
+        async fn from_child(leaf: &Leaf) -> Self {
+            leaf.computations.fetch_add(1, Ordering::SeqCst);
+            // give the other tasks time to await the annotation concurrently
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            Value(leaf.value)
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn tasks_share_one_computation() {
+        let annotated = Arc::new(AsyncAnnotated::<_, Value>::new(Leaf {
+            value: 42,
+            computations: AtomicUsize::new(0),
+        }));
+
+        let tasks: Vec<_> = (0..8)
+            .map(|_| {
+                let annotated = annotated.clone();
+                tokio::spawn(async move { annotated.anno().await.0 })
+            })
+            .collect();
+
+        for task in tasks {
+            assert_eq!(task.await.unwrap(), 42);
+        }
+        assert_eq!(annotated.child().computations.load(Ordering::SeqCst), 1);
+        assert_eq!(annotated.get_anno(), Some(&Value(42)));
+    }
+}
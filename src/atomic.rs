@@ -0,0 +1,515 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use core::cell::UnsafeCell;
+use core::fmt;
+use core::mem::{self, ManuallyDrop, MaybeUninit};
+use core::ptr;
+
+#[cfg(not(loom))]
+use core::hint::spin_loop;
+#[cfg(not(loom))]
+use core::sync::atomic::{self, AtomicU8};
+#[cfg(loom)]
+use loom::hint::spin_loop;
+#[cfg(loom)]
+use loom::sync::atomic::{self, AtomicU8};
+
+use crate::Annotation;
+
+const EMPTY: u8 = 0;
+const COMPUTING: u8 = 1;
+const READY: u8 = 2;
+
+/// A child annotated with some metadata, safe to share between threads
+/// without locking.
+///
+/// The annotation is lazily computed when first asked for using [`anno`].
+/// Threads racing to compute it coordinate through an atomic state machine,
+/// with exactly one of them computing the annotation while the others spin
+/// until it is ready. This makes it suited for read-mostly structures where
+/// annotations are cheap to compute, or computed ahead of time.
+///
+/// [`anno`]: AtomicAnnotated::anno
+pub struct AtomicAnnotated<C, A> {
+    child: C,
+    state: AtomicU8,
+    anno: UnsafeCell<MaybeUninit<A>>,
+}
+
+// SAFETY: the annotation is only ever written by the thread that
+// successfully transitions the state from `EMPTY` to `COMPUTING`, and only
+// read after the state is observed to be `READY`.
+unsafe impl<C, A> Sync for AtomicAnnotated<C, A>
+where
+    C: Sync,
+    A: Send + Sync,
+{
+}
+
+impl<C, A> AtomicAnnotated<C, A> {
+    /// Returns the annotated child.
+    pub fn child(&self) -> &C {
+        &self.child
+    }
+
+    /// Returns the annotation over the child, if it was already computed.
+    pub fn get_anno(&self) -> Option<&A> {
+        match self.state.load(atomic::Ordering::Acquire) {
+            // SAFETY: the annotation is initialized in the `READY` state
+            READY => Some(unsafe { (*self.anno.get()).assume_init_ref() }),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the annotation over the child is already computed.
+    pub fn is_computed(&self) -> bool {
+        self.state.load(atomic::Ordering::Acquire) == READY
+    }
+
+    /// Invalidate the annotation, forcing it to be re-computed the next time
+    /// it is asked for.
+    pub fn invalidate(&mut self) {
+        self.take_anno();
+    }
+
+    /// Replace the child with a new one, returning the old child.
+    ///
+    /// The annotation is invalidated.
+    pub fn replace_child(&mut self, child: C) -> C {
+        self.invalidate();
+        mem::replace(&mut self.child, child)
+    }
+
+    /// Consume the structure and return the child and the annotation, if it
+    /// was already computed.
+    pub fn split(mut self) -> (C, Option<A>) {
+        let anno = self.take_anno();
+
+        // SAFETY: the structure is never dropped, so the child is only read
+        // once, and the annotation is already taken out
+        let this = ManuallyDrop::new(self);
+        let child = unsafe { ptr::read(&this.child) };
+
+        (child, anno)
+    }
+
+    /// Consume the structure and return the child, discarding the annotation.
+    pub fn into_child(self) -> C {
+        self.split().0
+    }
+
+    fn take_anno(&mut self) -> Option<A> {
+        match replace_state(&mut self.state, EMPTY) {
+            // SAFETY: the annotation is initialized in the `READY` state, and
+            // the state is now `EMPTY`, so it won't be read again
+            READY => Some(unsafe { self.anno.get_mut().assume_init_read() }),
+            _ => None,
+        }
+    }
+}
+
+impl<C, A> AtomicAnnotated<C, A>
+where
+    A: Annotation<C>,
+{
+    /// Create a new annotation over a child.
+    pub fn new(child: C) -> Self {
+        Self {
+            child,
+            state: AtomicU8::new(EMPTY),
+            anno: UnsafeCell::new(MaybeUninit::uninit()),
+        }
+    }
+
+    /// Create a new annotated child with an already computed annotation.
+    ///
+    /// The annotation is trusted to be the correct one for the child, and
+    /// will not be recomputed until the child is mutated.
+    pub fn with_anno(child: C, anno: A) -> Self {
+        Self {
+            child,
+            state: AtomicU8::new(READY),
+            anno: UnsafeCell::new(MaybeUninit::new(anno)),
+        }
+    }
+
+    /// Returns the annotation over the child.
+    ///
+    /// If the annotation is being computed by another thread, this will spin
+    /// until it is ready.
+    pub fn anno(&self) -> &A {
+        loop {
+            if let Some(anno) = self.get_anno() {
+                return anno;
+            }
+
+            let won = self
+                .state
+                .compare_exchange(
+                    EMPTY,
+                    COMPUTING,
+                    atomic::Ordering::Acquire,
+                    atomic::Ordering::Acquire,
+                )
+                .is_ok();
+
+            if !won {
+                spin_loop();
+                continue;
+            }
+
+            // if the computation panics the state is reset, allowing other
+            // threads to retry
+            let reset = ResetOnDrop(&self.state);
+            let anno = A::from_child(&self.child);
+            mem::forget(reset);
+
+            // SAFETY: only the thread that transitioned the state to
+            // `COMPUTING` writes the annotation, and no thread reads it until
+            // the state is `READY`
+            unsafe { (*self.anno.get()).write(anno) };
+            self.state.store(READY, atomic::Ordering::Release);
+        }
+    }
+
+    /// Returns a mutable reference to the annotated child.
+    pub fn child_mut(&mut self) -> AtomicAnnotatedRefMut<'_, C, A> {
+        AtomicAnnotatedRefMut { annotated: self }
+    }
+}
+
+#[cfg(not(loom))]
+fn replace_state(state: &mut AtomicU8, new: u8) -> u8 {
+    mem::replace(state.get_mut(), new)
+}
+
+#[cfg(loom)]
+fn replace_state(state: &mut AtomicU8, new: u8) -> u8 {
+    state.with_mut(|state| mem::replace(state, new))
+}
+
+struct ResetOnDrop<'a>(&'a AtomicU8);
+
+impl<'a> Drop for ResetOnDrop<'a> {
+    fn drop(&mut self) {
+        self.0.store(EMPTY, atomic::Ordering::Release);
+    }
+}
+
+impl<C, A> Drop for AtomicAnnotated<C, A> {
+    fn drop(&mut self) {
+        self.take_anno();
+    }
+}
+
+impl<C, A> fmt::Debug for AtomicAnnotated<C, A>
+where
+    C: fmt::Debug,
+    A: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AtomicAnnotated")
+            .field("child", &self.child)
+            .field("anno", &self.get_anno())
+            .finish()
+    }
+}
+
+impl<C, A> Clone for AtomicAnnotated<C, A>
+where
+    C: Clone,
+    A: Clone + Annotation<C>,
+{
+    fn clone(&self) -> Self {
+        // the annotation is cloned as well, to avoid re-computing it
+        let child = self.child.clone();
+        match self.get_anno() {
+            Some(anno) => Self::with_anno(child, anno.clone()),
+            None => Self::new(child),
+        }
+    }
+}
+
+crate::macros::impl_by_child!(AtomicAnnotated<C, A>);
+crate::macros::impl_from_child!(AtomicAnnotated<C, A>, Annotation);
+
+crate::macros::child_ref_mut! {
+    /// A mutable reference to a lock-free annotated child.
+    AtomicAnnotatedRefMut => AtomicAnnotated<C, A>
+}
+
+#[cfg(all(test, not(loom)))]
+mod tests {
+    extern crate std;
+
+    use core::cell::Cell;
+
+    use super::*;
+
+    /// Annotation counting how many times it was computed, and dropped.
+    #[derive(Debug)]
+    struct Tracked<'a> {
+        value: u64,
+        drops: &'a Cell<usize>,
+    }
+
+    impl Drop for Tracked<'_> {
+        fn drop(&mut self) {
+            self.drops.set(self.drops.get() + 1);
+        }
+    }
+
+    struct Leaf<'a> {
+        value: u64,
+        drops: &'a Cell<usize>,
+        anno_drops: &'a Cell<usize>,
+    }
+
+    impl Drop for Leaf<'_> {
+        fn drop(&mut self) {
+            self.drops.set(self.drops.get() + 1);
+        }
+    }
+
+    impl<'a> Annotation<Leaf<'a>> for Tracked<'a> {
+        fn from_child(leaf: &Leaf<'a>) -> Self {
+            Tracked {
+                value: leaf.value,
+                drops: leaf.anno_drops,
+            }
+        }
+    }
+
+    fn leaf<'a>(
+        drops: &'a Cell<usize>,
+        anno_drops: &'a Cell<usize>,
+    ) -> Leaf<'a> {
+        Leaf {
+            value: 42,
+            drops,
+            anno_drops,
+        }
+    }
+
+    #[test]
+    fn drop_drops_child_and_computed_annotation_once() {
+        let (drops, anno_drops) = (Cell::new(0), Cell::new(0));
+
+        let annotated =
+            AtomicAnnotated::<_, Tracked>::new(leaf(&drops, &anno_drops));
+        drop(annotated);
+        assert_eq!((drops.get(), anno_drops.get()), (1, 0));
+
+        let annotated =
+            AtomicAnnotated::<_, Tracked>::new(leaf(&drops, &anno_drops));
+        assert_eq!(annotated.anno().value, 42);
+        drop(annotated);
+        assert_eq!((drops.get(), anno_drops.get()), (2, 1));
+    }
+
+    #[test]
+    fn split_moves_out_without_dropping() {
+        let (drops, anno_drops) = (Cell::new(0), Cell::new(0));
+
+        let annotated =
+            AtomicAnnotated::<_, Tracked>::new(leaf(&drops, &anno_drops));
+        annotated.anno();
+
+        let (child, anno) = annotated.split();
+        assert_eq!((drops.get(), anno_drops.get()), (0, 0));
+        assert_eq!(anno.as_ref().map(|anno| anno.value), Some(42));
+
+        drop(child);
+        drop(anno);
+        assert_eq!((drops.get(), anno_drops.get()), (1, 1));
+
+        let annotated =
+            AtomicAnnotated::<_, Tracked>::new(leaf(&drops, &anno_drops));
+        let (child, anno) = annotated.split();
+        assert!(anno.is_none());
+        drop(child);
+        assert_eq!((drops.get(), anno_drops.get()), (2, 1));
+    }
+
+    #[test]
+    fn into_child_drops_annotation_once() {
+        let (drops, anno_drops) = (Cell::new(0), Cell::new(0));
+
+        let annotated =
+            AtomicAnnotated::<_, Tracked>::new(leaf(&drops, &anno_drops));
+        annotated.anno();
+
+        let child = annotated.into_child();
+        assert_eq!((drops.get(), anno_drops.get()), (0, 1));
+        drop(child);
+        assert_eq!((drops.get(), anno_drops.get()), (1, 1));
+    }
+
+    #[test]
+    fn invalidating_drops_annotation_once() {
+        let (drops, anno_drops) = (Cell::new(0), Cell::new(0));
+
+        let mut annotated =
+            AtomicAnnotated::<_, Tracked>::new(leaf(&drops, &anno_drops));
+        annotated.anno();
+
+        annotated.invalidate();
+        assert!(!annotated.is_computed());
+        assert_eq!(anno_drops.get(), 1);
+
+        annotated.child_mut().value = 7;
+        assert_eq!(anno_drops.get(), 1);
+        assert_eq!(annotated.anno().value, 7);
+
+        drop(annotated);
+        assert_eq!((drops.get(), anno_drops.get()), (1, 2));
+    }
+
+    mod threads {
+        use core::sync::atomic::{AtomicBool, AtomicUsize};
+        use std::panic::{self, AssertUnwindSafe};
+        use std::sync::Barrier;
+        use std::thread;
+        use std::time::Duration;
+        use std::vec::Vec;
+
+        use super::*;
+
+        const THREADS: usize = 8;
+
+        #[derive(Debug, PartialEq)]
+        struct Value(u64);
+
+        struct Leaf {
+            value: u64,
+            computations: AtomicUsize,
+            fail: AtomicBool,
+        }
+
+        impl Leaf {
+            fn new(value: u64) -> Self {
+                Self {
+                    value,
+                    computations: AtomicUsize::new(0),
+                    fail: AtomicBool::new(false),
+                }
+            }
+        }
+
+        impl Annotation<Leaf> for Value {
+            fn from_child(leaf: &Leaf) -> Self {
+                leaf.computations.fetch_add(1, atomic::Ordering::SeqCst);
+                if leaf.fail.load(atomic::Ordering::SeqCst) {
+                    panic!("failed computing the annotation");
+                }
+
+                // widen the window for the other threads to race
+                thread::sleep(Duration::from_millis(10));
+                Value(leaf.value)
+            }
+        }
+
+        #[test]
+        fn racing_readers_compute_once() {
+            let annotated = AtomicAnnotated::<_, Value>::new(Leaf::new(42));
+            let barrier = Barrier::new(THREADS);
+
+            let annos: Vec<&Value> = thread::scope(|s| {
+                let handles: Vec<_> = (0..THREADS)
+                    .map(|_| {
+                        s.spawn(|| {
+                            barrier.wait();
+                            annotated.anno()
+                        })
+                    })
+                    .collect();
+
+                handles.into_iter().map(|h| h.join().unwrap()).collect()
+            });
+
+            let computations = &annotated.child().computations;
+            assert_eq!(computations.load(atomic::Ordering::SeqCst), 1);
+
+            assert!(annos.iter().all(|anno| ptr::eq(*anno, annos[0])));
+            assert_eq!(annos[0], &Value(42));
+        }
+
+        #[test]
+        fn panicking_computation_resets_state() {
+            let leaf = Leaf::new(42);
+            leaf.fail.store(true, atomic::Ordering::SeqCst);
+            let annotated = AtomicAnnotated::<_, Value>::new(leaf);
+
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                annotated.anno();
+            }));
+            assert!(result.is_err());
+            assert!(!annotated.is_computed());
+            assert_eq!(annotated.get_anno(), None);
+
+            // waiting threads aren't left spinning, and one of them retries
+            annotated
+                .child()
+                .fail
+                .store(false, atomic::Ordering::SeqCst);
+            thread::scope(|s| {
+                for _ in 0..THREADS {
+                    s.spawn(|| assert_eq!(annotated.anno(), &Value(42)));
+                }
+            });
+
+            let computations = &annotated.child().computations;
+            assert_eq!(computations.load(atomic::Ordering::SeqCst), 2);
+        }
+    }
+}
+
+// run using `RUSTFLAGS="--cfg loom" cargo test --lib atomic::model`
+#[cfg(all(test, loom))]
+mod model {
+    use loom::sync::atomic::AtomicUsize;
+    use loom::sync::Arc;
+    use loom::thread;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Value(u64);
+
+    struct Leaf {
+        value: u64,
+        computations: AtomicUsize,
+    }
+
+    impl Annotation<Leaf> for Value {
+        fn from_child(leaf: &Leaf) -> Self {
+            leaf.computations.fetch_add(1, atomic::Ordering::SeqCst);
+            Value(leaf.value)
+        }
+    }
+
+    #[test]
+    fn racing_readers_compute_once() {
+        loom::model(|| {
+            let annotated = Arc::new(AtomicAnnotated::<_, Value>::new(Leaf {
+                value: 42,
+                computations: AtomicUsize::new(0),
+            }));
+
+            let other = Arc::clone(&annotated);
+            let handle = thread::spawn(move || {
+                assert_eq!(other.anno(), &Value(42));
+            });
+
+            assert_eq!(annotated.anno(), &Value(42));
+            handle.join().unwrap();
+
+            let computations = &annotated.child().computations;
+            assert_eq!(computations.load(atomic::Ordering::SeqCst), 1);
+        });
+    }
+}
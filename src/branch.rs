@@ -0,0 +1,451 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use core::borrow::{Borrow, BorrowMut};
+use core::cell::Ref;
+use core::fmt;
+use core::marker::PhantomData;
+use core::ops::{Deref, DerefMut};
+
+use crate::compound::{ChildMut, ChildRef, Compound, CompoundMut};
+use crate::walk::{Step, Walker};
+use crate::Annotation;
+
+/// A path from the root of a [`Compound`] structure to one of its leaves.
+///
+/// The annotations over the nodes descended into are retained, from the
+/// root down to the leaf, and the branch de-references to the leaf.
+///
+/// # Example
+/// ```
+/// use ranno::anno::Cardinality;
+/// use ranno::compound::{ChildOf, ChildRef, Compound};
+/// use ranno::{combine, Annotated, Annotation, Branch};
+///
+/// enum Tree {
+///     Leaf(u64),
+///     Node(Vec<Annotated<Tree, Cardinality>>),
+/// }
+///
+/// impl Annotation<Tree> for Cardinality {
+///     fn from_child(tree: &Tree) -> Self {
+///         match tree {
+///             Tree::Leaf(leaf) => Cardinality::from_leaf(leaf),
+///             Tree::Node(children) => combine::combine_children(children),
+///         }
+///     }
+/// }
+///
+/// impl Compound<Cardinality> for Tree {
+///     type Leaf = u64;
+///     type Node = Tree;
+///
+///     fn child(&self, i: usize) -> Option<ChildOf<'_, Self, Cardinality>> {
+///         match self {
+///             Tree::Leaf(leaf) => (i == 0).then_some(ChildRef::Leaf(leaf)),
+///             Tree::Node(nodes) => nodes.get(i).map(ChildRef::Node),
+///         }
+///     }
+/// }
+///
+/// let tree = Tree::Node(vec![
+///     Annotated::new(Tree::Node(vec![])),
+///     Annotated::new(Tree::Leaf(1)),
+///     Annotated::new(Tree::Leaf(2)),
+/// ]);
+///
+/// let branch = Branch::first(&tree).unwrap();
+///
+/// assert_eq!(*branch, 1);
+/// assert_eq!(branch.path().collect::<Vec<_>>(), [1, 0]);
+/// assert_eq!(branch.annos().collect::<Vec<_>>(), [&Cardinality(1)]);
+/// ```
+pub struct Branch<'a, C, A>
+where
+    C: Compound<A>,
+{
+    path: Vec<(&'a C, usize)>,
+    annos: Vec<Ref<'a, A>>,
+    leaf: &'a C::Leaf,
+}
+
+impl<'a, C, A> Branch<'a, C, A>
+where
+    C: Compound<A>,
+    A: Annotation<C::Node>,
+{
+    /// Returns the branch to the first leaf of the structure, or `None` if it
+    /// has no leaves.
+    pub fn first(root: &'a C) -> Option<Self> {
+        let (path, leaf) = first_leaf(root)?;
+        Some(Self::from_path(path, leaf))
+    }
+
+    /// Returns the branch to the leaf reached by descending into the children
+    /// chosen by the walker, or `None` if it aborts or chooses a child that
+    /// doesn't exist.
+    pub fn walk<W>(root: &'a C, mut walker: W) -> Option<Self>
+    where
+        A: Annotation<C::Leaf>,
+        W: Walker<C, A>,
+    {
+        let mut path = Vec::new();
+        let mut annos = Vec::new();
+
+        let mut node = root;
+        loop {
+            let children: Vec<_> = node
+                .children()
+                .map(|child| match child {
+                    ChildRef::Leaf(leaf) => {
+                        ChildAnno::Leaf(A::from_child(leaf))
+                    }
+                    ChildRef::Node(annotated) => {
+                        ChildAnno::Node(annotated.anno())
+                    }
+                })
+                .collect();
+
+            let index = match walker.walk(children.iter().map(|a| &**a)) {
+                Step::Found(index) => index,
+                Step::Abort => return None,
+            };
+            path.push((node, index));
+
+            match node.child(index)? {
+                ChildRef::Leaf(leaf) => {
+                    return Some(Self { path, annos, leaf });
+                }
+                ChildRef::Node(annotated) => {
+                    annos.push(annotated.anno());
+                    node = annotated.child().borrow();
+                }
+            }
+        }
+    }
+
+    /// Build a branch from the nodes on the path to a leaf and the index of
+    /// the child descended into at each of them, computing the annotations
+    /// of the child nodes.
+    pub(crate) fn from_path(
+        path: Vec<(&'a C, usize)>,
+        leaf: &'a C::Leaf,
+    ) -> Self {
+        let annos = path[..path.len() - 1]
+            .iter()
+            .filter_map(|(node, index)| node.child(*index)?.node())
+            .map(|annotated| annotated.anno())
+            .collect();
+
+        Self { path, annos, leaf }
+    }
+}
+
+impl<'a, C, A> Branch<'a, C, A>
+where
+    C: Compound<A>,
+{
+    /// Returns the root of the structure.
+    pub fn root(&self) -> &'a C {
+        self.path[0].0
+    }
+
+    /// Returns the leaf the branch leads to.
+    pub fn leaf(&self) -> &'a C::Leaf {
+        self.leaf
+    }
+
+    /// Returns the number of nodes on the branch, including the root.
+    pub fn depth(&self) -> usize {
+        self.path.len()
+    }
+
+    /// Returns the index of the child descended into at each node, from the
+    /// root down to the leaf.
+    pub fn path(&self) -> impl Iterator<Item = usize> + '_ {
+        self.path.iter().map(|(_, index)| *index)
+    }
+
+    /// Returns the annotations over the nodes descended into, from the child
+    /// of the root down to the parent of the leaf.
+    pub fn annos(&self) -> impl Iterator<Item = &A> {
+        self.annos.iter().map(|anno| &**anno)
+    }
+}
+
+impl<'a, C, A> Deref for Branch<'a, C, A>
+where
+    C: Compound<A>,
+{
+    type Target = C::Leaf;
+
+    fn deref(&self) -> &Self::Target {
+        self.leaf
+    }
+}
+
+impl<'a, C, A> fmt::Debug for Branch<'a, C, A>
+where
+    C: Compound<A>,
+    C::Leaf: fmt::Debug,
+    A: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Branch")
+            .field("path", &self.path().collect::<Vec<_>>())
+            .field("annos", &self.annos().collect::<Vec<_>>())
+            .field("leaf", &self.leaf)
+            .finish()
+    }
+}
+
+/// A mutable path from the root of a [`Compound`] structure to one of its
+/// leaves.
+///
+/// The branch de-references to the leaf. When it is mutably de-referenced,
+/// the annotations over all the nodes on the path are invalidated, keeping
+/// the structure consistent once the branch is dropped.
+///
+/// # Example
+/// ```
+/// use ranno::anno::Sum;
+/// use ranno::compound::{
+///     ChildMut, ChildMutOf, ChildOf, ChildRef, Compound, CompoundMut,
+/// };
+/// use ranno::{combine, Annotated, Annotation, BranchMut};
+///
+/// enum Tree {
+///     Leaf(u64),
+///     Node(Vec<Annotated<Tree, Sum<u64>>>),
+/// }
+///
+/// impl Annotation<Tree> for Sum<u64> {
+///     fn from_child(tree: &Tree) -> Self {
+///         match tree {
+///             Tree::Leaf(leaf) => Sum::from_leaf(leaf),
+///             Tree::Node(children) => combine::combine_children(children),
+///         }
+///     }
+/// }
+///
+/// impl Compound<Sum<u64>> for Tree {
+///     type Leaf = u64;
+///     type Node = Tree;
+///
+///     fn child(&self, i: usize) -> Option<ChildOf<'_, Self, Sum<u64>>> {
+///         match self {
+///             Tree::Leaf(leaf) => (i == 0).then_some(ChildRef::Leaf(leaf)),
+///             Tree::Node(nodes) => nodes.get(i).map(ChildRef::Node),
+///         }
+///     }
+/// }
+///
+/// impl CompoundMut<Sum<u64>> for Tree {
+///     fn child_mut(
+///         &mut self,
+///         i: usize,
+///     ) -> Option<ChildMutOf<'_, Self, Sum<u64>>> {
+///         match self {
+///             Tree::Leaf(leaf) => (i == 0).then_some(ChildMut::Leaf(leaf)),
+///             Tree::Node(nodes) => nodes.get_mut(i).map(ChildMut::Node),
+///         }
+///     }
+/// }
+///
+/// let mut tree = Tree::Node(vec![
+///     Annotated::new(Tree::Leaf(1)),
+///     Annotated::new(Tree::Leaf(2)),
+/// ]);
+/// assert_eq!(Sum::from_child(&tree), Sum(3));
+///
+/// let mut branch = BranchMut::first(&mut tree).unwrap();
+/// *branch = 5;
+///
+/// assert_eq!(Sum::from_child(&tree), Sum(7));
+/// ```
+pub struct BranchMut<'a, C, A> {
+    root: &'a mut C,
+    path: Vec<usize>,
+    anno: PhantomData<&'a A>,
+}
+
+impl<'a, C, A> BranchMut<'a, C, A>
+where
+    C: CompoundMut<A>,
+{
+    /// Returns the branch to the first leaf of the structure, or `None` if it
+    /// has no leaves.
+    pub fn first(root: &'a mut C) -> Option<Self> {
+        let (path, _) = first_leaf(&*root)?;
+        let path = path.iter().map(|(_, index)| *index).collect();
+        Some(Self::from_path(root, path))
+    }
+
+    /// Returns the branch to the leaf reached by descending into the children
+    /// chosen by the walker, or `None` if it aborts or chooses a child that
+    /// doesn't exist.
+    pub fn walk<W>(root: &'a mut C, walker: W) -> Option<Self>
+    where
+        A: Annotation<C::Leaf> + Annotation<C::Node>,
+        W: Walker<C, A>,
+    {
+        let branch = Branch::walk(&*root, walker)?;
+        let path = branch.path().collect();
+        drop(branch);
+        Some(Self::from_path(root, path))
+    }
+
+    /// Build a branch from the index of the child descended into at each node
+    /// on the path to a leaf, which must be a valid one.
+    pub(crate) fn from_path(root: &'a mut C, path: Vec<usize>) -> Self {
+        Self {
+            root,
+            path,
+            anno: PhantomData,
+        }
+    }
+
+    /// Returns the index of the child descended into at each node, from the
+    /// root down to the leaf.
+    pub fn path(&self) -> impl Iterator<Item = usize> + '_ {
+        self.path.iter().copied()
+    }
+
+    /// Returns the number of nodes on the branch, including the root.
+    pub fn depth(&self) -> usize {
+        self.path.len()
+    }
+
+    /// Returns a mutable reference to the leaf the branch leads to.
+    ///
+    /// The annotations over all the nodes on the path are invalidated.
+    pub fn leaf_mut(&mut self) -> &mut C::Leaf {
+        // the path can't be empty, and it leads to a leaf since the structure
+        // can't be changed while it is borrowed
+        let (last, path) = self.path.split_last().unwrap();
+
+        let mut node: &mut C = self.root;
+        for index in path {
+            match node.child_mut(*index) {
+                Some(ChildMut::Node(annotated)) => {
+                    annotated.invalidate();
+                    node = annotated.child.borrow_mut();
+                }
+                _ => unreachable!("the path leads through child nodes"),
+            }
+        }
+
+        match node.child_mut(*last) {
+            Some(ChildMut::Leaf(leaf)) => leaf,
+            _ => unreachable!("the path leads to a leaf"),
+        }
+    }
+}
+
+impl<'a, C, A> Deref for BranchMut<'a, C, A>
+where
+    C: CompoundMut<A>,
+{
+    type Target = C::Leaf;
+
+    fn deref(&self) -> &Self::Target {
+        // the path can't be empty, and it leads to a leaf since the structure
+        // can't be changed while it is borrowed
+        let (last, path) = self.path.split_last().unwrap();
+
+        let mut node: &C = self.root;
+        for index in path {
+            match node.child(*index) {
+                Some(ChildRef::Node(annotated)) => {
+                    node = annotated.child().borrow();
+                }
+                _ => unreachable!("the path leads through child nodes"),
+            }
+        }
+
+        match node.child(*last) {
+            Some(ChildRef::Leaf(leaf)) => leaf,
+            _ => unreachable!("the path leads to a leaf"),
+        }
+    }
+}
+
+impl<'a, C, A> DerefMut for BranchMut<'a, C, A>
+where
+    C: CompoundMut<A>,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.leaf_mut()
+    }
+}
+
+impl<'a, C, A> fmt::Debug for BranchMut<'a, C, A>
+where
+    C: CompoundMut<A>,
+    C::Leaf: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BranchMut")
+            .field("path", &self.path)
+            .field("leaf", &**self)
+            .finish()
+    }
+}
+
+/// Returns the nodes on the path to the first leaf of the structure, with the
+/// index of the child descended into at each of them, together with the
+/// leaf.
+#[allow(clippy::type_complexity)]
+fn first_leaf<'a, C, A>(
+    root: &'a C,
+) -> Option<(Vec<(&'a C, usize)>, &'a C::Leaf)>
+where
+    C: Compound<A>,
+    A: 'a,
+{
+    let mut path = Vec::from([(root, 0)]);
+
+    loop {
+        let (node, index) = *path.last()?;
+
+        match node.child(index) {
+            Some(ChildRef::Leaf(leaf)) => return Some((path, leaf)),
+            Some(ChildRef::Node(annotated)) => {
+                path.push((annotated.child().borrow(), 0));
+            }
+            None => {
+                // the node has no more children, so the next child of its
+                // parent is tried
+                path.pop();
+                if let Some((_, index)) = path.last_mut() {
+                    *index += 1;
+                }
+            }
+        }
+    }
+}
+
+/// The annotation over a child, either computed from a leaf or borrowed from
+/// an annotated node.
+enum ChildAnno<'a, A> {
+    Leaf(A),
+    Node(Ref<'a, A>),
+}
+
+impl<'a, A> Deref for ChildAnno<'a, A> {
+    type Target = A;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Self::Leaf(anno) => anno,
+            Self::Node(anno) => anno,
+        }
+    }
+}
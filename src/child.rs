@@ -0,0 +1,260 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use core::cell::Ref;
+use core::ops::{Deref, DerefMut};
+
+use crate::{Annotated, AnnotatedRefMut, Annotation};
+
+#[cfg(target_has_atomic = "8")]
+use crate::{AtomicAnnotated, AtomicAnnotatedRefMut};
+#[cfg(feature = "std")]
+use crate::{
+    LockedAnnoRef, LockedAnnotated, LockedAnnotatedRefMut, SyncAnnotated,
+    SyncAnnotatedRefMut,
+};
+
+/// The API shared by the types annotating a child, allowing generic code to
+/// switch between them without being rewritten.
+///
+/// This is implemented by [`Annotated`], as well as by its counterparts safe
+/// to share between threads, such as [`AtomicAnnotated`]. Each of them
+/// exposes the same methods inherently, so this trait only needs to be in
+/// scope when writing code generic over them.
+///
+/// # Example
+/// ```
+/// use ranno::{Annotated, AnnotatedChild, Annotation, AtomicAnnotated};
+///
+/// struct Leaf(u64);
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Double(u64);
+///
+/// impl Annotation<Leaf> for Double {
+///     fn from_child(leaf: &Leaf) -> Self {
+///         Double(2 * leaf.0)
+///     }
+/// }
+///
+/// fn increment<T>(annotated: &mut T) -> u64
+/// where
+///     T: AnnotatedChild<Leaf, Double>,
+/// {
+///     annotated.child_mut().0 += 1;
+///     annotated.anno().0
+/// }
+///
+/// let mut annotated = Annotated::<_, Double>::new(Leaf(1));
+/// assert_eq!(increment(&mut annotated), 4);
+///
+/// let mut annotated = AtomicAnnotated::<_, Double>::new(Leaf(1));
+/// assert_eq!(increment(&mut annotated), 4);
+/// ```
+///
+/// [`AtomicAnnotated`]: crate::AtomicAnnotated
+pub trait AnnotatedChild<C, A>: Sized
+where
+    A: Annotation<C>,
+{
+    /// A reference to the annotation.
+    type AnnoRef<'a>: Deref<Target = A>
+    where
+        Self: 'a;
+
+    /// A mutable reference to the child, invalidating the annotation when
+    /// mutably de-referenced.
+    type ChildMut<'a>: DerefMut<Target = C>
+    where
+        Self: 'a;
+
+    /// Create a new annotation over a child.
+    fn new(child: C) -> Self;
+
+    /// Create a new annotated child with an already computed annotation.
+    ///
+    /// The annotation is trusted to be the correct one for the child, and
+    /// will not be recomputed until the child is mutated.
+    fn with_anno(child: C, anno: A) -> Self;
+
+    /// Returns the annotated child.
+    fn child(&self) -> &C;
+
+    /// Returns the annotation over the child, computing it if it wasn't
+    /// already.
+    fn anno(&self) -> Self::AnnoRef<'_>;
+
+    /// Returns the annotation over the child, if it was already computed.
+    fn get_anno(&self) -> Option<Self::AnnoRef<'_>>;
+
+    /// Returns true if the annotation over the child is already computed.
+    fn is_computed(&self) -> bool;
+
+    /// Returns a mutable reference to the annotated child.
+    fn child_mut(&mut self) -> Self::ChildMut<'_>;
+
+    /// Invalidate the annotation, forcing it to be re-computed the next time
+    /// it is asked for.
+    fn invalidate(&mut self);
+
+    /// Replace the child with a new one, returning the old child.
+    ///
+    /// The annotation is invalidated.
+    fn replace_child(&mut self, child: C) -> C;
+
+    /// Consume the structure and return the child and the annotation, if it
+    /// was already computed.
+    fn split(self) -> (C, Option<A>);
+
+    /// Consume the structure and return the child, discarding the annotation.
+    fn into_child(self) -> C;
+}
+
+// Implements the trait by delegating to the inherent methods of the type.
+macro_rules! impl_annotated_child {
+    ($ty:ident, $anno_ref:ty, $child_mut:ty) => {
+        impl<C, A> AnnotatedChild<C, A> for $ty<C, A>
+        where
+            A: Annotation<C>,
+        {
+            type AnnoRef<'a>
+                = $anno_ref
+            where
+                Self: 'a;
+            type ChildMut<'a>
+                = $child_mut
+            where
+                Self: 'a;
+
+            fn new(child: C) -> Self {
+                Self::new(child)
+            }
+
+            fn with_anno(child: C, anno: A) -> Self {
+                Self::with_anno(child, anno)
+            }
+
+            fn child(&self) -> &C {
+                Self::child(self)
+            }
+
+            fn anno(&self) -> Self::AnnoRef<'_> {
+                Self::anno(self)
+            }
+
+            fn get_anno(&self) -> Option<Self::AnnoRef<'_>> {
+                Self::get_anno(self)
+            }
+
+            fn is_computed(&self) -> bool {
+                Self::is_computed(self)
+            }
+
+            fn child_mut(&mut self) -> Self::ChildMut<'_> {
+                Self::child_mut(self)
+            }
+
+            fn invalidate(&mut self) {
+                Self::invalidate(self)
+            }
+
+            fn replace_child(&mut self, child: C) -> C {
+                Self::replace_child(self, child)
+            }
+
+            fn split(self) -> (C, Option<A>) {
+                Self::split(self)
+            }
+
+            fn into_child(self) -> C {
+                Self::into_child(self)
+            }
+        }
+    };
+}
+
+impl_annotated_child!(Annotated, Ref<'a, A>, AnnotatedRefMut<'a, C, A>);
+
+#[cfg(target_has_atomic = "8")]
+impl_annotated_child!(AtomicAnnotated, &'a A, AtomicAnnotatedRefMut<'a, C, A>);
+
+#[cfg(feature = "std")]
+impl_annotated_child!(SyncAnnotated, &'a A, SyncAnnotatedRefMut<'a, C, A>);
+
+#[cfg(feature = "std")]
+impl_annotated_child!(
+    LockedAnnotated,
+    LockedAnnoRef<'a, A>,
+    LockedAnnotatedRefMut<'a, C, A>
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Leaf(u64);
+
+    #[derive(Debug, PartialEq)]
+    struct Double(u64);
+
+    impl Annotation<Leaf> for Double {
+        fn from_child(leaf: &Leaf) -> Self {
+            Double(2 * leaf.0)
+        }
+    }
+
+    fn check<T: AnnotatedChild<Leaf, Double>>() {
+        let mut annotated = T::new(Leaf(1));
+        assert!(!annotated.is_computed());
+        assert!(annotated.get_anno().is_none());
+        assert_eq!(*annotated.anno(), Double(2));
+        assert_eq!(annotated.get_anno().as_deref(), Some(&Double(2)));
+
+        annotated.child_mut().0 = 2;
+        assert!(!annotated.is_computed());
+        assert_eq!(*annotated.anno(), Double(4));
+
+        annotated.invalidate();
+        assert!(!annotated.is_computed());
+
+        let old = annotated.replace_child(Leaf(3));
+        assert_eq!(old.0, 2);
+        assert_eq!(annotated.child().0, 3);
+        assert_eq!(*annotated.anno(), Double(6));
+
+        let (child, anno) = annotated.split();
+        assert_eq!(child.0, 3);
+        assert_eq!(anno, Some(Double(6)));
+
+        // trusted annotations are never recomputed
+        let annotated = T::with_anno(Leaf(1), Double(7));
+        assert_eq!(*annotated.anno(), Double(7));
+        assert_eq!(annotated.into_child().0, 1);
+    }
+
+    #[test]
+    fn annotated() {
+        check::<Annotated<_, _>>();
+    }
+
+    #[cfg(target_has_atomic = "8")]
+    #[test]
+    fn atomic() {
+        check::<AtomicAnnotated<_, _>>();
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn sync() {
+        check::<SyncAnnotated<_, _>>();
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn locked() {
+        check::<LockedAnnotated<_, _>>();
+    }
+}
@@ -0,0 +1,203 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Associative aggregation of annotations.
+//!
+//! Many annotations over a parent can be computed purely from the annotations
+//! over its children, by combining them associatively. Implementing
+//! [`Combine`] for such an annotation allows for the parent annotation to be
+//! computed by folding over the already computed annotations of its
+//! children, without traversing further down the structure.
+//!
+//! # Example
+//! ```
+//! use ranno::combine::{self, Combine};
+//! use ranno::{Annotated, Annotation};
+//!
+//! #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+//! struct Sum(u64);
+//!
+//! impl Combine for Sum {
+//!     fn identity() -> Self {
+//!         Sum(0)
+//!     }
+//!
+//!     fn combine(&self, other: &Self) -> Self {
+//!         Sum(self.0 + other.0)
+//!     }
+//! }
+//!
+//! impl Annotation<u64> for Sum {
+//!     fn from_child(n: &u64) -> Self {
+//!         Sum(*n)
+//!     }
+//! }
+//!
+//! let children: [Annotated<u64, Sum>; 3] =
+//!     [1, 2, 3].map(Annotated::new);
+//!
+//! assert_eq!(combine::combine_children(&children), Sum(6));
+//! ```
+
+use core::marker::PhantomData;
+
+use crate::{Annotated, Annotation};
+
+/// An annotation that can be combined associatively with others of its type.
+///
+/// Implementations must ensure that `combine` is associative, and that
+/// combining with the [`identity`] leaves an annotation unchanged.
+///
+/// [`identity`]: Combine::identity
+pub trait Combine {
+    /// Returns the identity element of the combination.
+    fn identity() -> Self;
+
+    /// Combine the annotation with another.
+    fn combine(&self, other: &Self) -> Self;
+}
+
+/// Combine all the given annotations, in order.
+///
+/// Returns the identity if no annotations are given.
+pub fn combine_all<'a, A, I>(annos: I) -> A
+where
+    A: Combine + 'a,
+    I: IntoIterator<Item = &'a A>,
+{
+    annos
+        .into_iter()
+        .fold(A::identity(), |acc, anno| acc.combine(anno))
+}
+
+/// Combine the annotations of all the given annotated children, in order,
+/// computing them if needed.
+///
+/// Returns the identity if no children are given.
+pub fn combine_children<'a, C, A, I>(children: I) -> A
+where
+    C: 'a,
+    A: Combine + Annotation<C> + 'a,
+    I: IntoIterator<Item = &'a Annotated<C, A>>,
+{
+    children
+        .into_iter()
+        .fold(A::identity(), |acc, child| acc.combine(&child.anno()))
+}
+
+/// Types with a smallest value.
+///
+/// This is used as the identity when taking the largest of some values, such
+/// as for fields marked with `#[combine(max)]` when deriving [`Combine`].
+/// Unlike [`Default`], it is correct for signed types.
+pub trait Minimum {
+    /// The smallest value of the type.
+    const MIN: Self;
+}
+
+macro_rules! integer_minimum {
+    ($($ty:ty),+) => {
+        $(
+            impl Minimum for $ty {
+                const MIN: Self = <$ty>::MIN;
+            }
+        )+
+    };
+}
+
+integer_minimum!(u8, u16, u32, u64, u128, usize);
+integer_minimum!(i8, i16, i32, i64, i128, isize);
+
+impl Minimum for bool {
+    const MIN: Self = false;
+}
+
+impl Minimum for char {
+    const MIN: Self = '\0';
+}
+
+/// `None` is smaller than any other value.
+impl<T> Minimum for Option<T> {
+    const MIN: Self = None;
+}
+
+/// The unit annotation carries no data, and can be used for structures that
+/// don't need any metadata. Since [`Annotation`] can't be implemented for it
+/// over any child, it should be implemented over the child in question:
+///
+/// ```
+/// use ranno::{Annotated, Annotation};
+///
+/// struct Tree;
+///
+/// impl Annotation<Tree> for () {
+///     fn from_child(_: &Tree) -> Self {}
+/// }
+///
+/// let tree = Annotated::<Tree, ()>::new(Tree);
+/// assert_eq!(*tree.anno(), ());
+/// ```
+impl Combine for () {
+    fn identity() -> Self {}
+
+    fn combine(&self, _: &Self) -> Self {}
+}
+
+/// Marker annotations carry no data, and can be used to tag structures with
+/// a type-level flavor. As with the unit annotation, [`Annotation`] should be
+/// implemented over the child in question:
+///
+/// ```
+/// use core::marker::PhantomData;
+/// use ranno::{Annotated, Annotation};
+///
+/// struct Tree;
+/// struct Sparse;
+///
+/// impl Annotation<Tree> for PhantomData<Sparse> {
+///     fn from_child(_: &Tree) -> Self {
+///         PhantomData
+///     }
+/// }
+///
+/// let tree = Annotated::<Tree, PhantomData<Sparse>>::new(Tree);
+/// assert_eq!(*tree.anno(), PhantomData);
+/// ```
+impl<T: ?Sized> Combine for PhantomData<T> {
+    fn identity() -> Self {
+        PhantomData
+    }
+
+    fn combine(&self, _: &Self) -> Self {
+        PhantomData
+    }
+}
+
+macro_rules! tuple_combine {
+    ($($elem:ident => $index:tt),+) => {
+        impl<$($elem),+> Combine for ($($elem,)+)
+        where
+            $($elem: Combine,)+
+        {
+            fn identity() -> Self {
+                ($($elem::identity(),)+)
+            }
+
+            fn combine(&self, other: &Self) -> Self {
+                ($(self.$index.combine(&other.$index),)+)
+            }
+        }
+    };
+}
+
+tuple_combine!(A0 => 0);
+tuple_combine!(A0 => 0, A1 => 1);
+tuple_combine!(A0 => 0, A1 => 1, A2 => 2);
+tuple_combine!(A0 => 0, A1 => 1, A2 => 2, A3 => 3);
+tuple_combine!(A0 => 0, A1 => 1, A2 => 2, A3 => 3, A4 => 4);
+tuple_combine!(A0 => 0, A1 => 1, A2 => 2, A3 => 3, A4 => 4, A5 => 5);
+tuple_combine!(A0 => 0, A1 => 1, A2 => 2, A3 => 3, A4 => 4, A5 => 5, A6 => 6);
+tuple_combine!(A0 => 0, A1 => 1, A2 => 2, A3 => 3, A4 => 4, A5 => 5, A6 => 6, A7 => 7);
@@ -0,0 +1,159 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Projection of composite annotations onto their components.
+//!
+//! Annotations composed of several others, such as tuples of annotations,
+//! can be queried for one of their components using [`AnnoComponent`]. This
+//! allows for code generic over the annotation to ask for the part it is
+//! interested in, without knowing the concrete annotation type.
+//!
+//! The second type parameter of [`AnnoComponent`] is an index describing
+//! where the component is located. It is inferred by the compiler and should
+//! be left generic, since the component may be found at a different location
+//! in each annotation type.
+//!
+//! # Example
+//! ```
+//! use ranno::anno::{MaxKey, MinKey};
+//! use ranno::component::{self, AnnoComponent};
+//!
+//! fn max_key<A, I>(anno: &A) -> Option<&u32>
+//! where
+//!     A: AnnoComponent<MaxKey<u32>, I>,
+//! {
+//!     component::component::<MaxKey<u32>, _, _>(anno).key()
+//! }
+//!
+//! let anno = (MinKey::Minimum(1), (MaxKey::Maximum(7), 5u64));
+//! assert_eq!(max_key(&anno), Some(&7));
+//! ```
+
+use core::borrow::Borrow;
+use core::marker::PhantomData;
+
+/// An annotation containing a component of type `T`, located at index `I`.
+pub trait AnnoComponent<T, I = Borrowed> {
+    /// Returns the component of the annotation.
+    fn component(&self) -> &T;
+}
+
+/// Returns the component of type `T` of the given annotation.
+pub fn component<T, I, A>(anno: &A) -> &T
+where
+    A: AnnoComponent<T, I>,
+{
+    anno.component()
+}
+
+/// Index of a component obtained by borrowing the annotation itself.
+#[derive(Debug)]
+pub enum Borrowed {}
+
+impl<T, A> AnnoComponent<T, Borrowed> for A
+where
+    A: Borrow<T>,
+{
+    fn component(&self) -> &T {
+        self.borrow()
+    }
+}
+
+macro_rules! field_index {
+    ($($field:ident => $nth:literal),+) => {
+        $(
+            #[doc = concat!(
+                "Index of a component found at index `I` of the ",
+                $nth,
+                " element of a tuple."
+            )]
+            #[derive(Debug)]
+            pub struct $field<I>(PhantomData<I>);
+        )+
+    };
+}
+
+field_index! {
+    Field0 => "first",
+    Field1 => "second",
+    Field2 => "third",
+    Field3 => "fourth",
+    Field4 => "fifth",
+    Field5 => "sixth",
+    Field6 => "seventh",
+    Field7 => "eighth"
+}
+
+/// Index of a component found at index `I` of the left annotation of a
+/// [`Combined`].
+///
+/// [`Combined`]: crate::anno::Combined
+#[derive(Debug)]
+pub struct Left<I>(PhantomData<I>);
+
+/// Index of a component found at index `I` of the right annotation of a
+/// [`Combined`].
+///
+/// [`Combined`]: crate::anno::Combined
+#[derive(Debug)]
+pub struct Right<I>(PhantomData<I>);
+
+macro_rules! tuple_component {
+    ($field:ident, $index:tt, $target:ident; $($elem:ident),+) => {
+        impl<T, I, $($elem),+> AnnoComponent<T, $field<I>> for ($($elem,)+)
+        where
+            $target: AnnoComponent<T, I>,
+        {
+            fn component(&self) -> &T {
+                self.$index.component()
+            }
+        }
+    };
+}
+
+tuple_component!(Field0, 0, A0; A0);
+
+tuple_component!(Field0, 0, A0; A0, A1);
+tuple_component!(Field1, 1, A1; A0, A1);
+
+tuple_component!(Field0, 0, A0; A0, A1, A2);
+tuple_component!(Field1, 1, A1; A0, A1, A2);
+tuple_component!(Field2, 2, A2; A0, A1, A2);
+
+tuple_component!(Field0, 0, A0; A0, A1, A2, A3);
+tuple_component!(Field1, 1, A1; A0, A1, A2, A3);
+tuple_component!(Field2, 2, A2; A0, A1, A2, A3);
+tuple_component!(Field3, 3, A3; A0, A1, A2, A3);
+
+tuple_component!(Field0, 0, A0; A0, A1, A2, A3, A4);
+tuple_component!(Field1, 1, A1; A0, A1, A2, A3, A4);
+tuple_component!(Field2, 2, A2; A0, A1, A2, A3, A4);
+tuple_component!(Field3, 3, A3; A0, A1, A2, A3, A4);
+tuple_component!(Field4, 4, A4; A0, A1, A2, A3, A4);
+
+tuple_component!(Field0, 0, A0; A0, A1, A2, A3, A4, A5);
+tuple_component!(Field1, 1, A1; A0, A1, A2, A3, A4, A5);
+tuple_component!(Field2, 2, A2; A0, A1, A2, A3, A4, A5);
+tuple_component!(Field3, 3, A3; A0, A1, A2, A3, A4, A5);
+tuple_component!(Field4, 4, A4; A0, A1, A2, A3, A4, A5);
+tuple_component!(Field5, 5, A5; A0, A1, A2, A3, A4, A5);
+
+tuple_component!(Field0, 0, A0; A0, A1, A2, A3, A4, A5, A6);
+tuple_component!(Field1, 1, A1; A0, A1, A2, A3, A4, A5, A6);
+tuple_component!(Field2, 2, A2; A0, A1, A2, A3, A4, A5, A6);
+tuple_component!(Field3, 3, A3; A0, A1, A2, A3, A4, A5, A6);
+tuple_component!(Field4, 4, A4; A0, A1, A2, A3, A4, A5, A6);
+tuple_component!(Field5, 5, A5; A0, A1, A2, A3, A4, A5, A6);
+tuple_component!(Field6, 6, A6; A0, A1, A2, A3, A4, A5, A6);
+
+tuple_component!(Field0, 0, A0; A0, A1, A2, A3, A4, A5, A6, A7);
+tuple_component!(Field1, 1, A1; A0, A1, A2, A3, A4, A5, A6, A7);
+tuple_component!(Field2, 2, A2; A0, A1, A2, A3, A4, A5, A6, A7);
+tuple_component!(Field3, 3, A3; A0, A1, A2, A3, A4, A5, A6, A7);
+tuple_component!(Field4, 4, A4; A0, A1, A2, A3, A4, A5, A6, A7);
+tuple_component!(Field5, 5, A5; A0, A1, A2, A3, A4, A5, A6, A7);
+tuple_component!(Field6, 6, A6; A0, A1, A2, A3, A4, A5, A6, A7);
+tuple_component!(Field7, 7, A7; A0, A1, A2, A3, A4, A5, A6, A7);
@@ -0,0 +1,232 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Recursive structures exposing their children.
+//!
+//! Structures implementing [`Compound`] describe how each of their nodes
+//! holds leaves and annotated child nodes. This allows for the structure to
+//! be traversed generically, using the annotations over the child nodes to
+//! guide the traversal.
+//!
+#![cfg_attr(
+    feature = "alloc",
+    doc = r##"
+# Example
+```
+use ranno::anno::Cardinality;
+use ranno::compound::{ChildOf, ChildRef, Compound};
+use ranno::{combine, Annotated, Annotation};
+
+enum Tree {
+    Leaf(u64),
+    Node(Vec<Annotated<Tree, Cardinality>>),
+}
+
+impl Annotation<Tree> for Cardinality {
+    fn from_child(tree: &Tree) -> Self {
+        match tree {
+            Tree::Leaf(leaf) => Cardinality::from_leaf(leaf),
+            Tree::Node(children) => combine::combine_children(children),
+        }
+    }
+}
+
+impl Compound<Cardinality> for Tree {
+    type Leaf = u64;
+    type Node = Tree;
+
+    fn child(&self, i: usize) -> Option<ChildOf<'_, Self, Cardinality>> {
+        match self {
+            Tree::Leaf(leaf) => (i == 0).then_some(ChildRef::Leaf(leaf)),
+            Tree::Node(nodes) => nodes.get(i).map(ChildRef::Node),
+        }
+    }
+}
+
+let tree = Tree::Node(vec![
+    Annotated::new(Tree::Leaf(1)),
+    Annotated::new(Tree::Leaf(2)),
+]);
+
+assert_eq!(tree.children().count(), 2);
+assert!(tree.children().all(|child| child.is_node()));
+
+let leaves: Vec<_> = tree.iter().collect();
+assert_eq!(leaves, [&1, &2]);
+
+let leaves: Vec<_> = tree.iter_rev().collect();
+assert_eq!(leaves, [&2, &1]);
+```
+"##
+)]
+
+use core::borrow::{Borrow, BorrowMut};
+use core::fmt;
+#[cfg(feature = "alloc")]
+use core::iter::Rev;
+use core::marker::PhantomData;
+
+use crate::Annotated;
+
+#[cfg(feature = "alloc")]
+mod iter;
+#[cfg(feature = "alloc")]
+pub use iter::AnnotatedIter;
+
+#[cfg(feature = "derive")]
+pub use ranno_derive::{Compound, CompoundMut};
+
+/// A recursive structure whose nodes hold leaves and child nodes annotated
+/// with `A`.
+pub trait Compound<A>: Sized {
+    /// The type of the leaves of the structure.
+    type Leaf;
+
+    /// The type through which child nodes are held, such as `Self` or
+    /// `Box<Self>`.
+    type Node: Borrow<Self>;
+
+    /// Returns the child at the given index, or `None` if the index is past
+    /// the last child.
+    ///
+    /// Children must be at contiguous indices, starting from zero.
+    fn child(&self, index: usize) -> Option<ChildOf<'_, Self, A>>;
+
+    /// Returns an iterator over the children of the node, in order.
+    fn children(&self) -> Children<'_, Self, A> {
+        Children {
+            node: self,
+            index: 0,
+            anno: PhantomData,
+        }
+    }
+
+    /// Returns an iterator over the leaves of the structure, in depth-first
+    /// order.
+    #[cfg(feature = "alloc")]
+    fn iter(&self) -> AnnotatedIter<'_, Self, A> {
+        AnnotatedIter::new(self)
+    }
+
+    /// Returns an iterator over the leaves of the structure, in reverse
+    /// depth-first order.
+    #[cfg(feature = "alloc")]
+    fn iter_rev(&self) -> Rev<AnnotatedIter<'_, Self, A>> {
+        self.iter().rev()
+    }
+}
+
+/// A [`Compound`] structure whose children can be mutably accessed.
+///
+/// Mutating a child node through an [`Annotated`] requires invalidating its
+/// annotation, which is done by the code using this trait.
+pub trait CompoundMut<A>: Compound<A, Node: BorrowMut<Self>> {
+    /// Returns a mutable reference to the child at the given index, or `None`
+    /// if the index is past the last child.
+    fn child_mut(&mut self, index: usize) -> Option<ChildMutOf<'_, Self, A>>;
+}
+
+/// A reference to a child of a node of `C`, annotated with `A`.
+pub type ChildOf<'a, C, A> = ChildRef<
+    'a,
+    <C as Compound<A>>::Leaf,
+    Annotated<<C as Compound<A>>::Node, A>,
+>;
+
+/// A reference to a child of a node, either a leaf or an annotated node.
+#[derive(Debug)]
+pub enum ChildRef<'a, L, N> {
+    /// A leaf.
+    Leaf(&'a L),
+    /// An annotated child node.
+    Node(&'a N),
+}
+
+impl<'a, L, N> ChildRef<'a, L, N> {
+    /// Returns true if the child is a leaf.
+    pub fn is_leaf(&self) -> bool {
+        matches!(self, Self::Leaf(_))
+    }
+
+    /// Returns true if the child is a node.
+    pub fn is_node(&self) -> bool {
+        matches!(self, Self::Node(_))
+    }
+
+    /// Returns the leaf, if the child is one.
+    pub fn leaf(self) -> Option<&'a L> {
+        match self {
+            Self::Leaf(leaf) => Some(leaf),
+            Self::Node(_) => None,
+        }
+    }
+
+    /// Returns the node, if the child is one.
+    pub fn node(self) -> Option<&'a N> {
+        match self {
+            Self::Leaf(_) => None,
+            Self::Node(node) => Some(node),
+        }
+    }
+}
+
+impl<'a, L, N> Clone for ChildRef<'a, L, N> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, L, N> Copy for ChildRef<'a, L, N> {}
+
+/// A mutable reference to a child of a node of `C`, annotated with `A`.
+pub type ChildMutOf<'a, C, A> = ChildMut<
+    'a,
+    <C as Compound<A>>::Leaf,
+    Annotated<<C as Compound<A>>::Node, A>,
+>;
+
+/// A mutable reference to a child of a node, either a leaf or an annotated
+/// node.
+#[derive(Debug)]
+pub enum ChildMut<'a, L, N> {
+    /// A leaf.
+    Leaf(&'a mut L),
+    /// An annotated child node.
+    Node(&'a mut N),
+}
+
+/// Iterator over the children of a node.
+///
+/// This `struct` is created by [`Compound::children`].
+pub struct Children<'a, C, A> {
+    node: &'a C,
+    index: usize,
+    anno: PhantomData<A>,
+}
+
+impl<'a, C, A> Iterator for Children<'a, C, A>
+where
+    C: Compound<A>,
+    C::Leaf: 'a,
+    C::Node: 'a,
+    A: 'a,
+{
+    type Item = ChildOf<'a, C, A>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let child = self.node.child(self.index)?;
+        self.index += 1;
+        Some(child)
+    }
+}
+
+impl<'a, C, A> fmt::Debug for Children<'a, C, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Children")
+            .field("index", &self.index)
+            .finish_non_exhaustive()
+    }
+}
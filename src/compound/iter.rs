@@ -0,0 +1,247 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use core::borrow::Borrow;
+use core::fmt;
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+
+use crate::compound::{ChildRef, Compound};
+
+/// Iterator over the leaves of a [`Compound`] structure, in depth-first
+/// order.
+///
+/// The structure is traversed using an explicit stack, as opposed to
+/// recursively, and no annotations are computed. The iterator is
+/// double-ended, with leaves iterated from the back in reverse depth-first
+/// order.
+///
+/// This `struct` is created by [`Compound::iter`].
+pub struct AnnotatedIter<'a, C, A> {
+    // the index of the next child of each node on the path to the last leaf
+    // yielded from the front
+    front: Vec<(&'a C, usize)>,
+    // the index of the child of each node on the path to the last leaf
+    // yielded from the back, starting from the number of children of the root
+    back: Vec<(&'a C, usize)>,
+    anno: PhantomData<&'a A>,
+}
+
+impl<'a, C, A> AnnotatedIter<'a, C, A>
+where
+    C: Compound<A>,
+{
+    /// Create an iterator over the leaves of the structure with the given
+    /// root.
+    pub fn new(root: &'a C) -> Self {
+        Self {
+            front: Vec::from([(root, 0)]),
+            back: Vec::from([(root, root.children().count())]),
+            anno: PhantomData,
+        }
+    }
+
+    /// Returns true if the front has reached the back, in which case all
+    /// leaves were yielded.
+    ///
+    /// The leaf at the front is located one child behind the index at each
+    /// level, so the indices at the back are shifted by one to compare them.
+    fn is_crossed(&self) -> bool {
+        let front = self.front.iter().map(|(_, index)| *index);
+        let back = self.back.iter().map(|(_, index)| *index + 1);
+        front.ge(back)
+    }
+
+    fn finish(&mut self) {
+        self.front.clear();
+        self.back.clear();
+    }
+}
+
+impl<'a, C, A> Iterator for AnnotatedIter<'a, C, A>
+where
+    C: Compound<A>,
+{
+    type Item = &'a C::Leaf;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let Some((node, index)) = self.front.last_mut() else {
+                self.finish();
+                return None;
+            };
+            let child = node.child(*index);
+            *index += 1;
+
+            match child {
+                Some(ChildRef::Leaf(leaf)) => {
+                    if self.is_crossed() {
+                        self.finish();
+                        return None;
+                    }
+                    return Some(leaf);
+                }
+                Some(ChildRef::Node(annotated)) => {
+                    self.front.push((annotated.child().borrow(), 0));
+                }
+                None => {
+                    self.front.pop();
+                }
+            }
+        }
+    }
+}
+
+impl<'a, C, A> DoubleEndedIterator for AnnotatedIter<'a, C, A>
+where
+    C: Compound<A>,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        loop {
+            let Some((node, index)) = self.back.last_mut() else {
+                self.finish();
+                return None;
+            };
+            if *index == 0 {
+                self.back.pop();
+                continue;
+            }
+            *index -= 1;
+
+            // children before the end are always present
+            match node.child(*index) {
+                Some(ChildRef::Leaf(leaf)) => {
+                    if self.is_crossed() {
+                        self.finish();
+                        return None;
+                    }
+                    return Some(leaf);
+                }
+                Some(ChildRef::Node(annotated)) => {
+                    let node: &C = annotated.child().borrow();
+                    self.back.push((node, node.children().count()));
+                }
+                None => unreachable!("children are at contiguous indices"),
+            }
+        }
+    }
+}
+
+impl<'a, C, A> FusedIterator for AnnotatedIter<'a, C, A> where C: Compound<A> {}
+
+impl<'a, C, A> fmt::Debug for AnnotatedIter<'a, C, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let front: Vec<_> = self.front.iter().map(|(_, index)| index).collect();
+        let back: Vec<_> = self.back.iter().map(|(_, index)| index).collect();
+        f.debug_struct("AnnotatedIter")
+            .field("front", &front)
+            .field("back", &back)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::compound::ChildOf;
+    use crate::{Annotated, Annotation};
+
+    enum Tree {
+        Leaf(u64),
+        Node(Vec<Annotated<Tree, ()>>),
+    }
+
+    impl Annotation<Tree> for () {
+        fn from_child(_: &Tree) -> Self {}
+    }
+
+    impl Compound<()> for Tree {
+        type Leaf = u64;
+        type Node = Tree;
+
+        fn child(&self, i: usize) -> Option<ChildOf<'_, Self, ()>> {
+            match self {
+                Tree::Leaf(leaf) => (i == 0).then_some(ChildRef::Leaf(leaf)),
+                Tree::Node(nodes) => nodes.get(i).map(ChildRef::Node),
+            }
+        }
+    }
+
+    fn leaf(n: u64) -> Annotated<Tree, ()> {
+        Annotated::new(Tree::Leaf(n))
+    }
+
+    fn node<const N: usize>(
+        children: [Annotated<Tree, ()>; N],
+    ) -> Annotated<Tree, ()> {
+        Annotated::new(Tree::Node(Vec::from(children)))
+    }
+
+    /// A tree with ten leaves at different depths, with nodes mixing leaves
+    /// and other nodes, and some empty nodes.
+    fn uneven_tree() -> Tree {
+        Tree::Node(Vec::from([
+            leaf(0),
+            node([node([leaf(1), leaf(2)]), node([]), leaf(3)]),
+            node([]),
+            node([node([node([leaf(4)])])]),
+            leaf(5),
+            leaf(6),
+            node([leaf(7), node([leaf(8), node([leaf(9)])])]),
+            node([node([])]),
+        ]))
+    }
+
+    #[test]
+    fn iterates_in_depth_first_order() {
+        let tree = uneven_tree();
+
+        let leaves: Vec<_> = tree.iter().copied().collect();
+        assert_eq!(leaves, Vec::from_iter(0..10));
+
+        let leaves: Vec<_> = tree.iter_rev().copied().collect();
+        assert_eq!(leaves, Vec::from_iter((0..10).rev()));
+    }
+
+    #[test]
+    fn alternating_ends_yields_each_leaf_once() {
+        let tree = uneven_tree();
+
+        // each bit of the pattern picks the end to take the next leaf from
+        for pattern in 0u32..1 << 10 {
+            let mut iter = tree.iter();
+            let mut front = 0;
+            let mut back = 10;
+
+            for step in 0..10 {
+                if pattern & (1 << step) == 0 {
+                    assert_eq!(iter.next(), Some(&front));
+                    front += 1;
+                } else {
+                    back -= 1;
+                    assert_eq!(iter.next_back(), Some(&back));
+                }
+            }
+
+            assert_eq!(front, back);
+            assert_eq!(iter.next(), None);
+            assert_eq!(iter.next_back(), None);
+        }
+    }
+
+    #[test]
+    fn empty_tree_yields_nothing() {
+        let tree = Tree::Node(Vec::from([node([]), node([node([])])]));
+
+        assert_eq!(tree.iter().next(), None);
+        assert_eq!(tree.iter().next_back(), None);
+    }
+}
@@ -0,0 +1,211 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use core::cell::{Ref, RefCell};
+use core::mem;
+
+/// Annotation over a child, depending on some external context.
+pub trait AnnotationWith<C, X: ?Sized> {
+    /// Compute the annotation from the child and the context.
+    fn from_child_with(t: &C, ctx: &X) -> Self;
+}
+
+impl<'a, C, X, A> AnnotationWith<C, &'a X> for A
+where
+    X: ?Sized,
+    A: AnnotationWith<C, X>,
+{
+    fn from_child_with(t: &C, ctx: &&'a X) -> Self {
+        A::from_child_with(t, ctx)
+    }
+}
+
+/// A child annotated with some metadata, computed using a context stored
+/// alongside it.
+///
+/// This exposes the same API as [`Annotated`]. The context can be borrowed
+/// instead of stored by using a reference to it as `X`.
+///
+/// [`Annotated`]: crate::Annotated
+#[derive(Debug)]
+pub struct AnnotatedWith<C, A, X> {
+    child: C,
+    ctx: X,
+    anno: RefCell<Option<A>>,
+}
+
+impl<C, A, X> AnnotatedWith<C, A, X> {
+    /// Returns the annotated child.
+    pub fn child(&self) -> &C {
+        &self.child
+    }
+
+    /// Returns the context used to compute the annotation.
+    pub fn context(&self) -> &X {
+        &self.ctx
+    }
+
+    /// Returns the annotation over the child, if it was already computed.
+    pub fn get_anno(&self) -> Option<Ref<'_, A>> {
+        Ref::filter_map(self.anno.borrow(), Option::as_ref).ok()
+    }
+
+    /// Returns true if the annotation over the child is already computed.
+    pub fn is_computed(&self) -> bool {
+        self.anno.borrow().is_some()
+    }
+
+    /// Invalidate the annotation, forcing it to be re-computed the next time
+    /// it is asked for.
+    pub fn invalidate(&mut self) {
+        *self.anno.get_mut() = None;
+    }
+
+    /// Replace the child with a new one, returning the old child.
+    ///
+    /// The annotation is invalidated.
+    pub fn replace_child(&mut self, child: C) -> C {
+        self.invalidate();
+        mem::replace(&mut self.child, child)
+    }
+
+    /// Replace the context with a new one, returning the old context.
+    ///
+    /// The annotation is invalidated.
+    pub fn replace_context(&mut self, ctx: X) -> X {
+        self.invalidate();
+        mem::replace(&mut self.ctx, ctx)
+    }
+
+    /// Consume the structure and return the child, the context, and the
+    /// annotation, if it was already computed.
+    pub fn split(self) -> (C, X, Option<A>) {
+        (self.child, self.ctx, self.anno.into_inner())
+    }
+
+    /// Consume the structure and return the child, discarding the context
+    /// and the annotation.
+    pub fn into_child(self) -> C {
+        self.child
+    }
+}
+
+impl<C, A, X> AnnotatedWith<C, A, X>
+where
+    A: AnnotationWith<C, X>,
+{
+    /// Create a new annotation over a child, computed using the given
+    /// context.
+    pub fn new(child: C, ctx: X) -> Self {
+        Self {
+            anno: RefCell::new(None),
+            child,
+            ctx,
+        }
+    }
+
+    /// Create a new annotated child with an already computed annotation.
+    ///
+    /// The annotation is trusted to be the correct one for the child and the
+    /// context, and will not be recomputed until either is mutated.
+    pub fn with_anno(child: C, ctx: X, anno: A) -> Self {
+        Self {
+            anno: RefCell::new(Some(anno)),
+            child,
+            ctx,
+        }
+    }
+
+    /// Returns the annotation over the child.
+    ///
+    /// # Panics
+    /// If the annotation is computed while a reference to it is alive. This
+    /// can only happen if [`AnnotationWith::from_child_with`] re-enters the
+    /// same `AnnotatedWith`.
+    pub fn anno(&self) -> Ref<'_, A> {
+        if !self.is_computed() {
+            let anno = A::from_child_with(&self.child, &self.ctx);
+            *self.anno.borrow_mut() = Some(anno);
+        }
+
+        // unwrapping is ok since the annotation was just computed
+        self.get_anno().unwrap()
+    }
+
+    /// Returns a mutable reference to the annotated child.
+    pub fn child_mut(&mut self) -> AnnotatedWithRefMut<'_, C, A, X> {
+        AnnotatedWithRefMut { annotated: self }
+    }
+}
+
+impl<C, A, X> Clone for AnnotatedWith<C, A, X>
+where
+    C: Clone,
+    A: Clone,
+    X: Clone,
+{
+    fn clone(&self) -> Self {
+        // the annotation is cloned as well, to avoid re-computing it
+        Self {
+            child: self.child.clone(),
+            ctx: self.ctx.clone(),
+            anno: self.anno.clone(),
+        }
+    }
+}
+
+crate::macros::impl_by_child!(AnnotatedWith<C, A, X>);
+
+crate::macros::child_ref_mut! {
+    /// A mutable reference to a child annotated using a context.
+    AnnotatedWithRefMut => AnnotatedWith<C, A, X>
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The number of digits of a number in a given base.
+    #[derive(Debug, PartialEq)]
+    struct Digits(u32);
+
+    struct Base(u64);
+
+    impl AnnotationWith<u64, Base> for Digits {
+        fn from_child_with(n: &u64, base: &Base) -> Self {
+            Digits(n.checked_ilog(base.0).map_or(1, |log| log + 1))
+        }
+    }
+
+    #[test]
+    fn annotation_uses_context() {
+        let mut annotated = AnnotatedWith::<_, Digits, _>::new(255, Base(16));
+        assert_eq!(*annotated.anno(), Digits(2));
+
+        *annotated.child_mut() = 256;
+        assert!(!annotated.is_computed());
+        assert_eq!(*annotated.anno(), Digits(3));
+    }
+
+    #[test]
+    fn replace_context_invalidates_annotation() {
+        let mut annotated = AnnotatedWith::<_, Digits, _>::new(255, Base(16));
+        annotated.anno();
+        assert!(annotated.is_computed());
+
+        let old = annotated.replace_context(Base(2));
+        assert_eq!(old.0, 16);
+        assert!(!annotated.is_computed());
+        assert_eq!(*annotated.anno(), Digits(8));
+    }
+
+    #[test]
+    fn context_can_be_borrowed() {
+        let base = Base(10);
+        let annotated = AnnotatedWith::<_, Digits, _>::new(1000, &base);
+        assert_eq!(*annotated.anno(), Digits(4));
+    }
+}
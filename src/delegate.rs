@@ -0,0 +1,93 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use core::ops::{Deref, DerefMut};
+
+use crate::Annotation;
+
+/// A child behind any pointer, annotated with the annotation over the value
+/// it points to.
+///
+/// [`Annotation`] is implemented for children behind references and the
+/// standard smart pointers. Since it can't be implemented for every type
+/// implementing [`Deref`] without conflicting with implementations over
+/// other children, children behind other pointers, such as custom reference
+/// counted pointers, should be wrapped in this type to be annotated:
+///
+/// ```
+/// use core::ops::Deref;
+/// use ranno::anno::Sum;
+/// use ranno::{Annotated, Annotation, Delegated};
+///
+/// struct Leaf(u64);
+///
+/// impl Annotation<Leaf> for Sum<u64> {
+///     fn from_child(leaf: &Leaf) -> Self {
+///         Sum(leaf.0)
+///     }
+/// }
+///
+/// struct Handle<T>(T);
+///
+/// impl<T> Deref for Handle<T> {
+///     type Target = T;
+///
+///     fn deref(&self) -> &T {
+///         &self.0
+///     }
+/// }
+///
+/// let child = Delegated(Handle(Leaf(42)));
+/// let annotated = Annotated::<_, Sum<u64>>::new(child);
+///
+/// assert_eq!(*annotated.anno(), Sum(42));
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Delegated<P>(pub P);
+
+impl<P> Delegated<P> {
+    /// Consume the wrapper and return the pointer.
+    pub fn into_inner(self) -> P {
+        self.0
+    }
+}
+
+impl<P, A> Annotation<Delegated<P>> for A
+where
+    P: Deref,
+    A: Annotation<P::Target>,
+{
+    fn from_child(t: &Delegated<P>) -> Self {
+        A::from_child(&*t.0)
+    }
+
+    fn verify(t: &Delegated<P>, anno: &Self) -> bool
+    where
+        Self: PartialEq,
+    {
+        A::verify(&*t.0, anno)
+    }
+}
+
+impl<P> Deref for Delegated<P> {
+    type Target = P;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<P> DerefMut for Delegated<P> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<P> From<P> for Delegated<P> {
+    fn from(pointer: P) -> Self {
+        Self(pointer)
+    }
+}
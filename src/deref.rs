@@ -0,0 +1,108 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use core::cell::Ref;
+use core::ops::Deref;
+
+use crate::{Annotated, AnnotatedRefMut, Annotation};
+
+/// An [`Annotated`] that de-references to its child.
+///
+/// This allows for read access to the child without explicitly calling
+/// [`Annotated::child`]. Mutable access still goes through [`child_mut`], so
+/// the annotation is kept correct.
+///
+/// To avoid shadowing the methods of the child, functionality is exposed
+/// through associated functions, as in `AnnotatedDeref::anno(&annotated)`.
+///
+/// [`child_mut`]: AnnotatedDeref::child_mut
+#[derive(Debug)]
+pub struct AnnotatedDeref<C, A>(Annotated<C, A>);
+
+impl<C, A> AnnotatedDeref<C, A> {
+    /// Returns the wrapped annotated child.
+    pub fn annotated(this: &Self) -> &Annotated<C, A> {
+        &this.0
+    }
+
+    /// Returns the wrapped annotated child mutably.
+    pub fn annotated_mut(this: &mut Self) -> &mut Annotated<C, A> {
+        &mut this.0
+    }
+
+    /// Consume the structure and return the wrapped annotated child.
+    pub fn into_inner(this: Self) -> Annotated<C, A> {
+        this.0
+    }
+}
+
+impl<C, A> AnnotatedDeref<C, A>
+where
+    A: Annotation<C>,
+{
+    /// Create a new annotation over a child.
+    pub fn new(child: C) -> Self {
+        Self(Annotated::new(child))
+    }
+
+    /// Returns the annotation over the child.
+    pub fn anno(this: &Self) -> Ref<'_, A> {
+        this.0.anno()
+    }
+
+    /// Returns a mutable reference to the annotated child.
+    pub fn child_mut(this: &mut Self) -> AnnotatedRefMut<'_, C, A> {
+        this.0.child_mut()
+    }
+}
+
+impl<C, A> Deref for AnnotatedDeref<C, A> {
+    type Target = C;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.child()
+    }
+}
+
+impl<C, A> AsRef<C> for AnnotatedDeref<C, A> {
+    fn as_ref(&self) -> &C {
+        self.0.child()
+    }
+}
+
+impl<C, A> From<Annotated<C, A>> for AnnotatedDeref<C, A> {
+    fn from(annotated: Annotated<C, A>) -> Self {
+        Self(annotated)
+    }
+}
+
+impl<C, A> From<AnnotatedDeref<C, A>> for Annotated<C, A> {
+    fn from(annotated: AnnotatedDeref<C, A>) -> Self {
+        annotated.0
+    }
+}
+
+impl<C, A> Default for AnnotatedDeref<C, A>
+where
+    C: Default,
+    A: Annotation<C>,
+{
+    fn default() -> Self {
+        Self(Annotated::default())
+    }
+}
+
+impl<C, A> Clone for AnnotatedDeref<C, A>
+where
+    C: Clone,
+    A: Clone,
+{
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+crate::macros::impl_by_child!(AnnotatedDeref<C, A>, 0);
@@ -0,0 +1,29 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+use crate::{Annotated, Annotation};
+
+impl<'a, C, A> Arbitrary<'a> for Annotated<C, A>
+where
+    C: Arbitrary<'a>,
+    A: Arbitrary<'a> + Annotation<C>,
+{
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let child = C::arbitrary(u)?;
+
+        // the cache is either left empty, populated with the correct
+        // annotation, or populated with an arbitrary - likely stale - one
+        let anno = match u.int_in_range(0..=2u8)? {
+            0 => None,
+            1 => Some(A::from_child(&child)),
+            _ => Some(A::arbitrary(u)?),
+        };
+
+        Ok(Self::from_parts(child, anno))
+    }
+}
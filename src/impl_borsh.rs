@@ -0,0 +1,152 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use borsh::io::{Error, ErrorKind, Read, Result, Write};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::{Annotated, Annotation};
+
+// The child is laid out first, followed by the annotation as an option. The
+// annotation is always computed before serializing, making the layout
+// independent of whether it was already cached.
+
+/// The annotation is computed before serializing, if it wasn't already, so
+/// equal children are always serialized to the same bytes.
+impl<C, A> BorshSerialize for Annotated<C, A>
+where
+    C: BorshSerialize,
+    A: BorshSerialize + Annotation<C>,
+{
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let (child, anno) = self.child_and_anno();
+        child.serialize(writer)?;
+        Some(&*anno).serialize(writer)
+    }
+}
+
+/// The deserialized annotation is trusted to be the correct one for the
+/// child, as with [`Annotated::with_anno`]. When deserializing from an
+/// untrusted source, use [`Annotated::deserialize_verified`] instead.
+impl<C, A> BorshDeserialize for Annotated<C, A>
+where
+    C: BorshDeserialize,
+    A: BorshDeserialize,
+{
+    fn deserialize_reader<R: Read>(reader: &mut R) -> Result<Self> {
+        let child = C::deserialize_reader(reader)?;
+        let anno = Option::<A>::deserialize_reader(reader)?;
+
+        Ok(Self::from_parts(child, anno))
+    }
+}
+
+impl<C, A> Annotated<C, A>
+where
+    C: BorshDeserialize,
+    A: BorshDeserialize + Annotation<C> + PartialEq,
+{
+    /// Deserialize an annotated child, failing if the deserialized annotation
+    /// isn't the correct one for the child.
+    ///
+    /// The annotation is checked using [`Annotation::verify`]. By default,
+    /// this re-computes the annotation from the child, using the annotations
+    /// of any annotated children nested in it as they were deserialized.
+    ///
+    /// Only the top-level annotation is verified. The annotations of nested
+    /// children are trusted, and must be checked separately when they come
+    /// from an untrusted source.
+    pub fn deserialize_verified<R: Read>(reader: &mut R) -> Result<Self> {
+        let annotated = Self::deserialize_reader(reader)?;
+        if !annotated.verify_cache() {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "annotation doesn't match the child",
+            ));
+        }
+        Ok(annotated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Sum(u64);
+
+    impl Annotation<u64> for Sum {
+        fn from_child(n: &u64) -> Self {
+            Sum(*n)
+        }
+    }
+
+    impl BorshSerialize for Sum {
+        fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+            self.0.serialize(writer)
+        }
+    }
+
+    impl BorshDeserialize for Sum {
+        fn deserialize_reader<R: Read>(reader: &mut R) -> Result<Self> {
+            u64::deserialize_reader(reader).map(Sum)
+        }
+    }
+
+    fn to_vec<T: BorshSerialize>(value: &T) -> Vec<u8> {
+        borsh::to_vec(value).unwrap()
+    }
+
+    #[test]
+    fn serialization_is_independent_of_cache() {
+        let lazy = Annotated::<_, Sum>::new(42);
+        let forced = Annotated::<_, Sum>::new(42);
+        forced.force();
+
+        assert_eq!(to_vec(&lazy), to_vec(&forced));
+        assert_eq!(to_vec(&lazy), to_vec(&(42u64, Some(42u64))));
+    }
+
+    #[test]
+    fn round_trip_keeps_annotation() {
+        let annotated = Annotated::<_, Sum>::new(42);
+        let bytes = to_vec(&annotated);
+
+        let decoded = Annotated::<u64, Sum>::try_from_slice(&bytes).unwrap();
+        assert_eq!(decoded.child(), &42);
+        assert_eq!(decoded.get_anno().as_deref(), Some(&Sum(42)));
+    }
+
+    #[test]
+    fn missing_annotation_is_computed_lazily() {
+        let bytes = to_vec(&(42u64, None::<u64>));
+
+        let decoded = Annotated::<u64, Sum>::try_from_slice(&bytes).unwrap();
+        assert!(!decoded.is_computed());
+        assert_eq!(*decoded.anno(), Sum(42));
+    }
+
+    #[test]
+    fn verified_deserialization_rejects_wrong_annotation() {
+        let bytes = to_vec(&(42u64, Some(7u64)));
+
+        let trusted = Annotated::<u64, Sum>::try_from_slice(&bytes).unwrap();
+        assert_eq!(*trusted.anno(), Sum(7));
+
+        let err = Annotated::<u64, Sum>::deserialize_verified(&mut &bytes[..])
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        let bytes = to_vec(&(42u64, Some(42u64)));
+        let verified =
+            Annotated::<u64, Sum>::deserialize_verified(&mut &bytes[..])
+                .unwrap();
+        assert_eq!(*verified.anno(), Sum(42));
+    }
+}
@@ -0,0 +1,77 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use either::Either;
+
+use crate::Annotation;
+
+impl<L, R, A> Annotation<Either<L, R>> for A
+where
+    A: Annotation<L> + Annotation<R>,
+{
+    fn from_child(t: &Either<L, R>) -> Self {
+        match t {
+            Either::Left(l) => <A as Annotation<L>>::from_child(l),
+            Either::Right(r) => <A as Annotation<R>>::from_child(r),
+        }
+    }
+
+    fn verify(t: &Either<L, R>, anno: &Self) -> bool
+    where
+        Self: PartialEq,
+    {
+        match t {
+            Either::Left(l) => <A as Annotation<L>>::verify(l, anno),
+            Either::Right(r) => <A as Annotation<R>>::verify(r, anno),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::anno::Sum;
+    use crate::Annotated;
+
+    struct Small(u8);
+    struct Large(u64);
+
+    type Child = Either<Small, Large>;
+
+    impl Annotation<Small> for Sum<u64> {
+        fn from_child(small: &Small) -> Self {
+            Sum(u64::from(small.0))
+        }
+    }
+
+    impl Annotation<Large> for Sum<u64> {
+        fn from_child(large: &Large) -> Self {
+            Sum(large.0)
+        }
+    }
+
+    #[test]
+    fn either_is_annotated_as_its_side() {
+        let left = Annotated::<Child, Sum<u64>>::new(Either::Left(Small(3)));
+        let right = Annotated::<Child, Sum<u64>>::new(Either::Right(Large(7)));
+
+        assert_eq!(*left.anno(), Sum::from_child(&Small(3)));
+        assert_eq!(*right.anno(), Sum::from_child(&Large(7)));
+
+        assert!(left.verify_cache());
+        assert!(right.verify_cache());
+    }
+
+    #[test]
+    fn either_over_empty_children() {
+        let left: Either<[Small; 0], [Large; 0]> = Either::Left([]);
+        let right: Either<[Small; 0], [Large; 0]> = Either::Right([]);
+
+        assert_eq!(Sum::<u64>::from_child(&left), Sum(0));
+        assert_eq!(Sum::<u64>::from_child(&right), Sum(0));
+    }
+}
@@ -0,0 +1,34 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+
+use quickcheck::{Arbitrary, Gen};
+
+use crate::{Annotated, Annotation};
+
+impl<C, A> Arbitrary for Annotated<C, A>
+where
+    C: Arbitrary,
+    A: Clone + Annotation<C> + 'static,
+{
+    fn arbitrary(g: &mut Gen) -> Self {
+        let annotated = Self::new(C::arbitrary(g));
+
+        // randomly pre-populate the cache with the correct annotation
+        if bool::arbitrary(g) {
+            annotated.force();
+        }
+
+        annotated
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        Box::new(self.child.shrink().map(Self::new))
+    }
+}
@@ -0,0 +1,287 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+#[cfg(feature = "validation")]
+use core::{fmt, ptr};
+
+#[cfg(feature = "validation")]
+use rkyv::bytecheck::{CheckBytes, ErrorBox, StructCheckError};
+use rkyv::{out_field, Archive, Deserialize, Fallible, Serialize};
+
+use crate::{Annotated, Annotation};
+
+/// An archived [`Annotated`].
+///
+/// The annotation is always computed before archiving, meaning it can be read
+/// directly from the archive without deserializing the child.
+#[repr(C)]
+pub struct ArchivedAnnotated<C, A>
+where
+    C: Archive,
+    A: Archive,
+{
+    child: C::Archived,
+    anno: A::Archived,
+}
+
+impl<C, A> ArchivedAnnotated<C, A>
+where
+    C: Archive,
+    A: Archive,
+{
+    /// Returns the archived child.
+    pub fn child(&self) -> &C::Archived {
+        &self.child
+    }
+
+    /// Returns the archived annotation over the child.
+    pub fn anno(&self) -> &A::Archived {
+        &self.anno
+    }
+
+    /// Returns true if the archived annotation matches one computed over the
+    /// archived child.
+    ///
+    /// Archived annotations are trusted by default. This allows for them to
+    /// be verified when the archive comes from an untrusted source.
+    pub fn validate_anno(&self) -> bool
+    where
+        A: Annotation<C::Archived>,
+        A::Archived: PartialEq<A>,
+    {
+        self.anno == A::from_child(&self.child)
+    }
+}
+
+/// Returns true if the archived annotation of every given archived node
+/// matches one computed over its archived child.
+///
+/// Only the given nodes are validated. Computing the annotation over a child
+/// typically uses the archived annotations of the nodes nested in it, which
+/// are trusted unless they are given as well. Every node of the archived
+/// structure must then be given for all of its annotations to be validated.
+/// With the `validation` feature, archives checked using [`CheckBytes`] have
+/// their annotations validated recursively instead.
+///
+/// See [`ArchivedAnnotated::validate_anno`].
+///
+/// [`CheckBytes`]: rkyv::bytecheck::CheckBytes
+pub fn validate_annotations<'a, C, A, I>(nodes: I) -> bool
+where
+    C: Archive + 'a,
+    A: Archive + Annotation<C::Archived> + 'a,
+    A::Archived: PartialEq<A>,
+    I: IntoIterator<Item = &'a ArchivedAnnotated<C, A>>,
+{
+    nodes.into_iter().all(ArchivedAnnotated::validate_anno)
+}
+
+/// Error checking an [`ArchivedAnnotated`] whose archived annotation doesn't
+/// match the one computed over its archived child.
+#[cfg(feature = "validation")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnnoMismatch;
+
+#[cfg(feature = "validation")]
+impl fmt::Display for AnnoMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "archived annotation doesn't match its child")
+    }
+}
+
+#[cfg(feature = "validation")]
+impl core::error::Error for AnnoMismatch {}
+
+/// Checking an archived annotated child also validates its archived
+/// annotation, as with [`ArchivedAnnotated::validate_anno`]. The child is
+/// checked first, validating the annotations of the nodes nested in it before
+/// they are used to compute the annotation over it, so that no annotation in
+/// a checked archive is trusted.
+#[cfg(feature = "validation")]
+impl<C, A, Ctx> CheckBytes<Ctx> for ArchivedAnnotated<C, A>
+where
+    C: Archive,
+    C::Archived: CheckBytes<Ctx>,
+    A: Archive + Annotation<C::Archived>,
+    A::Archived: CheckBytes<Ctx> + PartialEq<A>,
+    Ctx: ?Sized,
+{
+    type Error = StructCheckError;
+
+    unsafe fn check_bytes<'a>(
+        value: *const Self,
+        context: &mut Ctx,
+    ) -> Result<&'a Self, Self::Error> {
+        C::Archived::check_bytes(ptr::addr_of!((*value).child), context)
+            .map_err(|e| StructCheckError {
+                field_name: "child",
+                inner: ErrorBox::new(e),
+            })?;
+        A::Archived::check_bytes(ptr::addr_of!((*value).anno), context)
+            .map_err(|e| StructCheckError {
+                field_name: "anno",
+                inner: ErrorBox::new(e),
+            })?;
+
+        let value = &*value;
+        if !value.validate_anno() {
+            return Err(StructCheckError {
+                field_name: "anno",
+                inner: ErrorBox::new(AnnoMismatch),
+            });
+        }
+
+        Ok(value)
+    }
+}
+
+/// The resolver for an archived [`Annotated`].
+pub struct AnnotatedResolver<C, A>
+where
+    C: Archive,
+    A: Archive,
+{
+    child: C::Resolver,
+    anno: A::Resolver,
+}
+
+impl<C, A> Archive for Annotated<C, A>
+where
+    C: Archive,
+    A: Archive + Annotation<C>,
+{
+    type Archived = ArchivedAnnotated<C, A>;
+    type Resolver = AnnotatedResolver<C, A>;
+
+    unsafe fn resolve(
+        &self,
+        pos: usize,
+        resolver: Self::Resolver,
+        out: *mut Self::Archived,
+    ) {
+        let (child, anno) = self.child_and_anno();
+
+        let (fp, fo) = out_field!(out.child);
+        child.resolve(pos + fp, resolver.child, fo);
+
+        let (fp, fo) = out_field!(out.anno);
+        anno.resolve(pos + fp, resolver.anno, fo);
+    }
+}
+
+impl<C, A, S> Serialize<S> for Annotated<C, A>
+where
+    C: Serialize<S>,
+    A: Serialize<S> + Annotation<C>,
+    S: Fallible + ?Sized,
+{
+    fn serialize(
+        &self,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        let (child, anno) = self.child_and_anno();
+
+        Ok(AnnotatedResolver {
+            child: child.serialize(serializer)?,
+            anno: anno.serialize(serializer)?,
+        })
+    }
+}
+
+impl<C, A, D> Deserialize<Annotated<C, A>, D> for ArchivedAnnotated<C, A>
+where
+    C: Archive,
+    C::Archived: Deserialize<C, D>,
+    A: Archive + Annotation<C>,
+    A::Archived: Deserialize<A, D>,
+    D: Fallible + ?Sized,
+{
+    fn deserialize(
+        &self,
+        deserializer: &mut D,
+    ) -> Result<Annotated<C, A>, D::Error> {
+        let child = self.child.deserialize(deserializer)?;
+        let anno = self.anno.deserialize(deserializer)?;
+
+        Ok(Annotated::with_anno(child, anno))
+    }
+}
+
+#[cfg(all(test, feature = "validation"))]
+mod tests {
+    extern crate alloc;
+
+    use alloc::vec::Vec;
+
+    use rkyv::vec::ArchivedVec;
+
+    use super::*;
+
+    type Leaf = Annotated<u64, u64>;
+    type Tree = Annotated<Vec<Leaf>, u64>;
+
+    impl Annotation<u64> for u64 {
+        fn from_child(n: &u64) -> Self {
+            *n
+        }
+    }
+
+    impl Annotation<Vec<Leaf>> for u64 {
+        fn from_child(leaves: &Vec<Leaf>) -> Self {
+            leaves.iter().map(|leaf| *leaf.anno()).sum()
+        }
+    }
+
+    impl Annotation<ArchivedVec<ArchivedAnnotated<u64, u64>>> for u64 {
+        fn from_child(
+            leaves: &ArchivedVec<ArchivedAnnotated<u64, u64>>,
+        ) -> Self {
+            leaves.iter().map(|leaf| *leaf.anno()).sum()
+        }
+    }
+
+    fn archive(tree: &Tree) -> rkyv::AlignedVec {
+        rkyv::to_bytes::<_, 256>(tree).unwrap()
+    }
+
+    #[test]
+    fn archived_annotations_are_computed() {
+        let tree = Tree::new((1..4).map(Leaf::new).collect());
+        let bytes = archive(&tree);
+
+        let archived = rkyv::check_archived_root::<Tree>(&bytes).unwrap();
+        assert_eq!(*archived.anno(), 6);
+        assert_eq!(archived.child().len(), 3);
+        assert!(validate_annotations(archived.child().iter()));
+    }
+
+    #[test]
+    fn checking_rejects_bogus_nested_annotations() {
+        let leaves = [Leaf::new(1), Leaf::with_anno(2, 20), Leaf::new(3)];
+        let tree = Tree::new(leaves.into_iter().collect());
+        let bytes = archive(&tree);
+
+        // the root is consistent with the bogus annotation of its leaf
+        let archived = unsafe { rkyv::archived_root::<Tree>(&bytes) };
+        assert_eq!(*archived.anno(), 24);
+        assert!(archived.validate_anno());
+        assert!(!validate_annotations(archived.child().iter()));
+
+        assert!(rkyv::check_archived_root::<Tree>(&bytes).is_err());
+    }
+
+    #[test]
+    fn deserializing_keeps_annotations() {
+        let tree = Tree::new((1..4).map(Leaf::new).collect());
+        let bytes = archive(&tree);
+
+        let archived = rkyv::check_archived_root::<Tree>(&bytes).unwrap();
+        let tree: Tree = archived.deserialize(&mut rkyv::Infallible).unwrap();
+        assert!(tree.is_computed());
+        assert_eq!(*tree.anno(), 6);
+        assert!(tree.child().iter().all(Leaf::is_computed));
+    }
+}
@@ -0,0 +1,154 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use parity_scale_codec::{Decode, Encode, EncodeLike, Error, Input, Output};
+
+use crate::{Annotated, Annotation};
+
+// The child is laid out first, followed by the annotation as an option. The
+// annotation is always computed before encoding, making the layout
+// independent of whether it was already cached.
+
+/// The annotation is computed before encoding, if it wasn't already, so
+/// equal children are always encoded to the same bytes.
+impl<C, A> Encode for Annotated<C, A>
+where
+    C: Encode,
+    A: Encode + Annotation<C>,
+{
+    fn size_hint(&self) -> usize {
+        let (child, anno) = self.child_and_anno();
+        child.size_hint() + Some(&*anno).size_hint()
+    }
+
+    fn encode_to<T: Output + ?Sized>(&self, dest: &mut T) {
+        let (child, anno) = self.child_and_anno();
+        child.encode_to(dest);
+        Some(&*anno).encode_to(dest);
+    }
+}
+
+impl<C, A> EncodeLike for Annotated<C, A>
+where
+    C: Encode,
+    A: Encode + Annotation<C>,
+{
+}
+
+/// The decoded annotation is trusted to be the correct one for the child, as
+/// with [`Annotated::with_anno`]. When decoding from an untrusted source, use
+/// [`Annotated::decode_verified`] instead.
+impl<C, A> Decode for Annotated<C, A>
+where
+    C: Decode,
+    A: Decode,
+{
+    fn decode<I: Input>(input: &mut I) -> Result<Self, Error> {
+        let child = C::decode(input)?;
+        let anno = Option::<A>::decode(input)?;
+
+        Ok(Self::from_parts(child, anno))
+    }
+}
+
+impl<C, A> Annotated<C, A>
+where
+    C: Decode,
+    A: Decode + Annotation<C> + PartialEq,
+{
+    /// Decode an annotated child, failing if the decoded annotation isn't the
+    /// correct one for the child.
+    ///
+    /// The annotation is checked using [`Annotation::verify`]. By default,
+    /// this re-computes the annotation from the child, using the annotations
+    /// of any annotated children nested in it as they were decoded.
+    ///
+    /// Only the top-level annotation is verified. The annotations of nested
+    /// children are trusted, and must be checked separately when they come
+    /// from an untrusted source.
+    pub fn decode_verified<I: Input>(input: &mut I) -> Result<Self, Error> {
+        let annotated = Self::decode(input)?;
+        if !annotated.verify_cache() {
+            return Err("annotation doesn't match the child".into());
+        }
+        Ok(annotated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Sum(u64);
+
+    impl Encode for Sum {
+        fn size_hint(&self) -> usize {
+            self.0.size_hint()
+        }
+
+        fn encode_to<T: Output + ?Sized>(&self, dest: &mut T) {
+            self.0.encode_to(dest);
+        }
+    }
+
+    impl Decode for Sum {
+        fn decode<I: Input>(input: &mut I) -> Result<Self, Error> {
+            u64::decode(input).map(Sum)
+        }
+    }
+
+    impl Annotation<u64> for Sum {
+        fn from_child(n: &u64) -> Self {
+            Sum(*n)
+        }
+    }
+
+    #[test]
+    fn encoding_is_independent_of_cache() {
+        let lazy = Annotated::<_, Sum>::new(42u64);
+        let forced = Annotated::<_, Sum>::new(42u64);
+        forced.force();
+
+        assert_eq!(lazy.encode(), forced.encode());
+        assert_eq!(lazy.encode(), (42u64, Some(42u64)).encode());
+        assert_eq!(lazy.size_hint(), lazy.encode().len());
+    }
+
+    #[test]
+    fn round_trip_keeps_annotation() {
+        let bytes = Annotated::<_, Sum>::new(42u64).encode();
+
+        let decoded = Annotated::<u64, Sum>::decode(&mut &bytes[..]).unwrap();
+        assert_eq!(decoded.child(), &42);
+        assert_eq!(decoded.get_anno().as_deref(), Some(&Sum(42)));
+    }
+
+    #[test]
+    fn missing_annotation_is_computed_lazily() {
+        let bytes = (42u64, None::<u64>).encode();
+
+        let decoded = Annotated::<u64, Sum>::decode(&mut &bytes[..]).unwrap();
+        assert!(!decoded.is_computed());
+        assert_eq!(*decoded.anno(), Sum(42));
+    }
+
+    #[test]
+    fn verified_decoding_rejects_wrong_annotation() {
+        let bytes = (42u64, Some(7u64)).encode();
+
+        let trusted = Annotated::<u64, Sum>::decode(&mut &bytes[..]).unwrap();
+        assert_eq!(*trusted.anno(), Sum(7));
+        assert!(
+            Annotated::<u64, Sum>::decode_verified(&mut &bytes[..]).is_err()
+        );
+
+        let bytes = (42u64, Some(42u64)).encode();
+        let verified =
+            Annotated::<u64, Sum>::decode_verified(&mut &bytes[..]).unwrap();
+        assert_eq!(*verified.anno(), Sum(42));
+    }
+}
@@ -23,11 +23,9 @@ extern crate alloc;
 use alloc::rc::Rc;
 
 use core::mem;
+use ranno::anno::Cardinality;
 use ranno::{Annotated, Annotation};
 
-#[derive(Debug, Default, Clone, PartialEq, Eq)]
-struct Cardinality(usize);
-
 impl<T> Annotation<LinkedList<T, Cardinality>> for Cardinality {
     fn from_child(list: &LinkedList<T, Cardinality>) -> Self {
         let c = match list {
@@ -110,9 +108,116 @@ assert_eq!(Cardinality::from_child(&list), Cardinality(1));
 #![deny(clippy::all)]
 #![cfg_attr(feature = "alloc", deny(missing_docs))]
 
-use core::cell::{Ref, RefCell};
-use core::cmp::Ordering;
-use core::ops::{Deref, DerefMut};
+#[cfg(feature = "std")]
+extern crate std;
+
+use core::borrow::Borrow;
+use core::cell::{Cell, Ref, RefCell};
+use core::fmt;
+use core::mem::{self, ManuallyDrop};
+use core::ops::{Deref, DerefMut, Index, IndexMut};
+use core::pin::Pin;
+
+mod macros;
+
+pub mod anno;
+pub mod combine;
+pub mod component;
+pub mod compound;
+pub mod store;
+pub mod walk;
+pub mod wire;
+
+pub use combine::Combine;
+pub use component::AnnoComponent;
+pub use compound::Compound;
+
+#[cfg(feature = "derive")]
+pub use ranno_derive::{annotation, Annotation, AnnotationDelegate, Combine};
+
+mod child;
+pub use child::AnnotatedChild;
+
+mod context;
+pub use context::{AnnotatedWith, AnnotatedWithRefMut, AnnotationWith};
+
+mod delegate;
+pub use delegate::Delegated;
+
+mod deref;
+pub use deref::AnnotatedDeref;
+
+mod try_anno;
+pub use try_anno::{TryAnnotated, TryAnnotatedRefMut, TryAnnotation};
+
+#[cfg(target_has_atomic = "8")]
+mod atomic;
+#[cfg(target_has_atomic = "8")]
+pub use atomic::{AtomicAnnotated, AtomicAnnotatedRefMut};
+
+#[cfg(feature = "std")]
+mod sync;
+#[cfg(feature = "std")]
+pub use sync::{SyncAnnotated, SyncAnnotatedRefMut};
+
+#[cfg(feature = "std")]
+mod panic;
+#[cfg(feature = "std")]
+pub use panic::AnnoPanic;
+
+#[cfg(feature = "metrics")]
+pub mod metrics;
+
+#[cfg(feature = "std")]
+mod locked;
+#[cfg(feature = "std")]
+pub use locked::{LockedAnnoRef, LockedAnnotated, LockedAnnotatedRefMut};
+
+#[cfg(feature = "alloc")]
+pub use impl_alloc::WeakAnnotated;
+
+#[cfg(feature = "alloc")]
+mod branch;
+#[cfg(feature = "alloc")]
+pub use branch::{Branch, BranchMut};
+
+#[cfg(feature = "alloc")]
+mod async_anno;
+#[cfg(feature = "alloc")]
+pub use async_anno::{AsyncAnnotated, AsyncAnnotatedRefMut, AsyncAnnotation};
+
+#[cfg(feature = "rayon")]
+pub mod par;
+
+#[cfg(feature = "serde")]
+pub mod serde_anno;
+
+#[cfg(feature = "rkyv")]
+mod impl_rkyv;
+#[cfg(feature = "validation")]
+pub use impl_rkyv::AnnoMismatch;
+#[cfg(feature = "rkyv")]
+pub use impl_rkyv::{
+    validate_annotations, AnnotatedResolver, ArchivedAnnotated,
+};
+
+#[cfg(feature = "borsh")]
+mod impl_borsh;
+
+#[cfg(feature = "scale")]
+mod impl_scale;
+
+#[cfg(feature = "arbitrary")]
+mod impl_arbitrary;
+
+#[cfg(feature = "either")]
+mod impl_either;
+
+#[cfg(feature = "proptest")]
+pub mod proptest;
+
+#[cfg(feature = "quickcheck")]
+mod impl_quickcheck;
 
 /// A child annotated with some metadata.
 ///
@@ -124,19 +229,185 @@ use core::ops::{Deref, DerefMut};
 pub struct Annotated<C, A> {
     child: C,
     anno: RefCell<Option<A>>,
+    generation: Cell<u64>,
 }
 
 impl<C, A> Annotated<C, A> {
-    /// Returns the annotation over the child.
+    fn from_parts(child: C, anno: Option<A>) -> Self {
+        Self {
+            child,
+            anno: RefCell::new(anno),
+            generation: Cell::new(0),
+        }
+    }
+
+    /// Returns the annotated child.
     pub fn child(&self) -> &C {
         &self.child
     }
 
+    /// Returns the annotation over the child, if it was already computed.
+    ///
+    /// Unlike [`anno`], this will never trigger the computation of the
+    /// annotation.
+    ///
+    /// [`anno`]: Annotated::anno
+    pub fn get_anno(&self) -> Option<Ref<'_, A>> {
+        Ref::filter_map(self.anno.borrow(), Option::as_ref).ok()
+    }
+
+    /// Returns true if the annotation over the child is already computed.
+    pub fn is_computed(&self) -> bool {
+        self.anno.borrow().is_some()
+    }
+
+    /// Returns the generation of the annotation.
+    ///
+    /// The generation is bumped every time the annotation is invalidated or
+    /// otherwise changed. Copies of the annotation made outside the structure
+    /// can be checked for staleness by comparing the generation at the time
+    /// of the copy with the current one.
+    pub fn generation(&self) -> u64 {
+        self.generation.get()
+    }
+
+    fn bump_generation(&self) {
+        self.generation.set(self.generation.get().wrapping_add(1));
+    }
+
+    /// Invalidate the annotation, forcing it to be re-computed the next time
+    /// it is asked for.
+    ///
+    /// This is useful when the child is mutated without going through
+    /// [`child_mut`], such as when using interior mutability.
+    ///
+    /// [`child_mut`]: Annotated::child_mut
+    pub fn invalidate(&mut self) {
+        *self.anno.get_mut() = None;
+        self.bump_generation();
+    }
+
+    /// Invalidate the annotation through a shared reference.
+    ///
+    /// # Panics
+    /// Panics if a reference to the annotation, obtained through
+    /// [`anno`] or [`get_anno`], is still alive.
+    ///
+    /// [`anno`]: Annotated::anno
+    /// [`get_anno`]: Annotated::get_anno
+    pub fn invalidate_shared(&self) {
+        self.anno.replace(None);
+        self.bump_generation();
+    }
+
+    /// Mutate the child using the given closure, which reports whether the
+    /// child was effectively changed.
+    ///
+    /// The annotation is only invalidated if the closure returns `true`
+    /// alongside its result.
+    pub fn child_mut_with<R, F>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut C) -> (R, bool),
+    {
+        let (r, changed) = f(&mut self.child);
+        if changed {
+            self.invalidate();
+        }
+        r
+    }
+
+    /// Set the annotation over the child through a shared reference,
+    /// skipping both its computation and the borrow checks on it.
+    ///
+    /// # Safety
+    /// The caller must ensure that no reference to the annotation, obtained
+    /// through [`anno`] or [`get_anno`], is alive during the call.
+    /// Furthermore, the annotation must be the correct one for the child,
+    /// since it will be trusted until the child is mutated.
+    ///
+    /// [`anno`]: Annotated::anno
+    /// [`get_anno`]: Annotated::get_anno
+    pub unsafe fn set_anno_unchecked(&self, anno: A) {
+        *self.anno.as_ptr() = Some(anno);
+        self.bump_generation();
+    }
+
+    /// Replace the child with a new one, returning the old child.
+    ///
+    /// The annotation is invalidated.
+    pub fn replace_child(&mut self, child: C) -> C {
+        self.invalidate();
+        mem::replace(&mut self.child, child)
+    }
+
     /// Consume the structure and return the child and the annotation, if it
     /// was already computed.
     pub fn split(self) -> (C, Option<A>) {
         (self.child, self.anno.take())
     }
+
+    /// Consume the structure and return the child, discarding the annotation.
+    pub fn into_child(self) -> C {
+        self.child
+    }
+
+    /// Consume the structure and return the annotation, if it was already
+    /// computed, discarding the child.
+    pub fn into_anno(self) -> Option<A> {
+        self.anno.into_inner()
+    }
+
+    /// Take the annotation out, if it was already computed, leaving the child
+    /// intact.
+    ///
+    /// The annotation will be re-computed the next time it is asked for.
+    pub fn take_anno(&mut self) -> Option<A> {
+        self.bump_generation();
+        self.anno.get_mut().take()
+    }
+
+    /// Maps the child to another type, discarding the annotation.
+    pub fn map_child<D, F>(self, f: F) -> Annotated<D, A>
+    where
+        F: FnOnce(C) -> D,
+    {
+        let annotated = Annotated::from_parts(f(self.child), None);
+        annotated.generation.set(self.generation.get());
+        annotated.bump_generation();
+        annotated
+    }
+
+    /// Maps the child to another type, keeping the annotation if it was
+    /// already computed.
+    ///
+    /// The mapping is trusted to preserve the annotation, i.e. computing the
+    /// annotation over the new child must yield the same result as over the
+    /// old one.
+    pub fn map_child_preserving<D, F>(self, f: F) -> Annotated<D, A>
+    where
+        F: FnOnce(C) -> D,
+    {
+        Annotated {
+            child: f(self.child),
+            anno: self.anno,
+            generation: self.generation,
+        }
+    }
+
+    /// Maps the annotation to another type, keeping the child.
+    ///
+    /// The closure is passed the annotation if it was already computed, and
+    /// may return the new annotation or `None` for it to be lazily computed.
+    pub fn map_anno<B, F>(self, f: F) -> Annotated<C, B>
+    where
+        F: FnOnce(Option<A>) -> Option<B>,
+    {
+        Annotated {
+            child: self.child,
+            anno: RefCell::new(f(self.anno.into_inner())),
+            generation: self.generation,
+        }
+    }
 }
 
 impl<C, A> Annotated<C, A>
@@ -145,87 +416,287 @@ where
 {
     /// Create a new annotation over a child.
     pub fn new(child: C) -> Self {
-        Self {
-            anno: RefCell::new(None),
-            child,
-        }
+        Self::from_parts(child, None)
     }
 
-    /// Returns the annotated child.
-    pub fn anno(&self) -> Ref<A> {
-        // lazily compute the annotation when reference is asked for
-        if self.anno.borrow().is_none() {
+    /// Create a new annotated child with an already computed annotation.
+    ///
+    /// The annotation is trusted to be the correct one for the child, and
+    /// will not be recomputed until the child is mutated.
+    pub fn with_anno(child: C, anno: A) -> Self {
+        Self::from_parts(child, Some(anno))
+    }
+
+    /// Returns the annotation over the child.
+    ///
+    /// # Panics
+    /// Panics if computing the annotation panics. The annotation is then left
+    /// uncomputed, and will be computed again the next time it is asked for.
+    pub fn anno(&self) -> Ref<'_, A> {
+        // lazily compute the annotation when reference is asked for. No
+        // borrow is held during the computation, and the cache is only
+        // written once it successfully returns, leaving it cleanly empty on
+        // panic.
+        if !self.is_computed() {
             let anno = A::from_child(&self.child);
-            self.anno.replace(Some(anno));
+            *self.anno.borrow_mut() = Some(anno);
         }
 
         // unwrapping is ok since we're sure the option is initialized
         Ref::map(self.anno.borrow(), |elem| elem.as_ref().unwrap())
     }
 
+    /// Returns both the child and the annotation over it.
+    pub fn child_and_anno(&self) -> (&C, Ref<'_, A>) {
+        (&self.child, self.anno())
+    }
+
+    /// Returns a copy of the annotation over the child.
+    ///
+    /// Unlike [`anno`], no borrow of the annotation is held after returning.
+    ///
+    /// [`anno`]: Annotated::anno
+    pub fn anno_copied(&self) -> A
+    where
+        A: Copy,
+    {
+        *self.anno()
+    }
+
+    /// Returns a clone of the annotation over the child.
+    ///
+    /// Unlike [`anno`], no borrow of the annotation is held after returning.
+    ///
+    /// [`anno`]: Annotated::anno
+    pub fn anno_cloned(&self) -> A
+    where
+        A: Clone,
+    {
+        self.anno().clone()
+    }
+
+    /// Returns the component of type `T` of the annotation over the child.
+    ///
+    /// See the [`component`] module for details.
+    ///
+    /// [`component`]: crate::component
+    pub fn anno_component<T, I>(&self) -> Ref<'_, T>
+    where
+        A: AnnoComponent<T, I>,
+    {
+        Ref::map(self.anno(), AnnoComponent::component)
+    }
+
+    /// Returns true if the cached annotation, if any, is the correct one for
+    /// the child.
+    ///
+    /// This is useful to audit annotations that were not computed locally,
+    /// such as those obtained through [`with_anno`], before trusting them.
+    ///
+    /// [`with_anno`]: Annotated::with_anno
+    pub fn verify_cache(&self) -> bool
+    where
+        A: PartialEq,
+    {
+        match self.get_anno() {
+            Some(anno) => A::verify(&self.child, &anno),
+            None => true,
+        }
+    }
+
+    /// Computes the annotation if it wasn't already, guaranteeing it is cached
+    /// afterwards.
+    ///
+    /// This allows for annotations to be eagerly computed, for instance while
+    /// constructing a structure, as opposed to when they are first queried.
+    pub fn force(&self) -> Ref<'_, A> {
+        self.anno()
+    }
+
     /// Returns a mutable reference to the annotated child.
-    pub fn child_mut(&mut self) -> AnnotatedRefMut<C, A> {
-        AnnotatedRefMut { annotated: self }
+    pub fn child_mut(&mut self) -> AnnotatedRefMut<'_, C, A> {
+        AnnotatedRefMut {
+            annotated: self,
+            recompute: None,
+        }
+    }
+
+    /// Returns a mutable reference to the annotated child, which eagerly
+    /// re-computes the annotation when dropped if it was invalidated.
+    ///
+    /// This moves the cost of computing the annotation to when the mutation
+    /// ends, as opposed to when it is next asked for.
+    pub fn child_mut_recompute(&mut self) -> AnnotatedRefMut<'_, C, A> {
+        AnnotatedRefMut {
+            annotated: self,
+            recompute: Some(A::from_child),
+        }
     }
 }
 
-impl<C, A> Default for Annotated<C, A>
+impl<C, A> Annotated<C, A>
 where
-    C: Default,
-    A: Annotation<C>,
+    A: DeltaAnnotation<C>,
 {
-    fn default() -> Self {
-        let elem = C::default();
-        Self::new(elem)
+    /// Mutate the child using the given closure, which returns a description
+    /// of the change made.
+    ///
+    /// If the annotation was already computed, it is patched using the
+    /// returned delta instead of being invalidated and re-computed. The
+    /// annotation is invalidated if the closure panics.
+    pub fn update<F>(&mut self, f: F)
+    where
+        F: FnOnce(&mut C) -> A::Delta,
+    {
+        // the annotation is only put back once the closure returns, so a
+        // panic doesn't leave it outdated
+        let mut anno = self.take_anno();
+        let delta = f(&mut self.child);
+
+        if let Some(anno) = &mut anno {
+            anno.apply_delta(&delta);
+        }
+        *self.anno.get_mut() = anno;
+    }
+}
+
+impl<C, A> Annotated<C, A> {
+    /// Insert a leaf into the child using the given closure, which returns
+    /// `None` if the leaf wasn't inserted, such as when it was already present.
+    ///
+    /// If the annotation was already computed, it is patched to account for
+    /// the leaf instead of being invalidated and re-computed. The annotation
+    /// is invalidated if the leaf isn't inserted or the closure panics.
+    pub fn insert_with<L, R, F>(&mut self, leaf: L, f: F) -> Option<R>
+    where
+        A: IncrementalAnnotation<C, L>,
+        F: FnOnce(&mut C, L) -> Option<R>,
+    {
+        // the leaf is moved into the closure, so the annotation is patched
+        // beforehand and only put back once the insertion succeeds
+        let mut anno = self.take_anno();
+        if let Some(anno) = &mut anno {
+            anno.on_insert(&leaf);
+        }
+
+        let r = f(&mut self.child, leaf)?;
+        *self.anno.get_mut() = anno;
+        Some(r)
+    }
+
+    /// Remove a leaf from the child using the given closure, which returns
+    /// the leaf removed, if any.
+    ///
+    /// If the annotation was already computed, it is patched to account for
+    /// the removed leaf instead of being invalidated and re-computed. The
+    /// annotation is invalidated if the closure panics.
+    pub fn remove_with<L, F>(&mut self, f: F) -> Option<L>
+    where
+        A: IncrementalAnnotation<C, L>,
+        F: FnOnce(&mut C) -> Option<L>,
+    {
+        // the annotation is only put back once the closure returns, so a
+        // panic doesn't leave it accounting for a removed leaf
+        let mut anno = self.take_anno();
+        let leaf = f(&mut self.child);
+
+        if let (Some(anno), Some(leaf)) = (&mut anno, &leaf) {
+            anno.on_remove(leaf);
+        }
+        *self.anno.get_mut() = anno;
+        leaf
     }
 }
 
 impl<C, A> Clone for Annotated<C, A>
 where
     C: Clone,
-    A: Annotation<C>,
+    A: Clone,
 {
     fn clone(&self) -> Self {
-        let child = self.child.clone();
-        Self::new(child)
+        // the annotation is cloned as well, to avoid re-computing it
+        Self {
+            child: self.child.clone(),
+            anno: self.anno.clone(),
+            generation: self.generation.clone(),
+        }
     }
 }
 
-impl<C, A> PartialEq for Annotated<C, A>
+crate::macros::impl_by_child!(Annotated<C, A>);
+crate::macros::impl_from_child!(Annotated<C, A>, Annotation);
+
+impl<C, A> fmt::Display for Annotated<C, A>
+where
+    C: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.child, f)
+    }
+}
+
+/// The annotation is trusted to be the correct one for the child, as with
+/// [`Annotated::with_anno`].
+impl<C, A> From<(C, A)> for Annotated<C, A>
 where
-    C: PartialEq,
+    A: Annotation<C>,
 {
-    fn eq(&self, other: &Self) -> bool {
-        PartialEq::eq(&self.child, &other.child)
+    fn from((child, anno): (C, A)) -> Self {
+        Self::with_anno(child, anno)
     }
 }
 
-impl<C, A> Eq for Annotated<C, A> where C: PartialEq + Eq {}
+impl<C, A> From<Annotated<C, A>> for (C, Option<A>) {
+    fn from(annotated: Annotated<C, A>) -> Self {
+        annotated.split()
+    }
+}
 
-impl<C, A> PartialOrd for Annotated<C, A>
+impl<'a, C, A> IntoIterator for &'a Annotated<C, A>
 where
-    C: PartialOrd,
+    &'a C: IntoIterator,
 {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        PartialOrd::partial_cmp(&self.child, &other.child)
+    type Item = <&'a C as IntoIterator>::Item;
+    type IntoIter = <&'a C as IntoIterator>::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.child.into_iter()
     }
 }
 
-impl<C, A> Ord for Annotated<C, A>
+impl<C, A, I> Index<I> for Annotated<C, A>
 where
-    C: PartialOrd + Ord,
+    C: Index<I>,
 {
-    fn cmp(&self, other: &Self) -> Ordering {
-        Ord::cmp(&self.child, &other.child)
+    type Output = C::Output;
+
+    fn index(&self, index: I) -> &Self::Output {
+        &self.child[index]
     }
 }
 
-impl<C, A> From<C> for Annotated<C, A>
+impl<C, A, I> IndexMut<I> for Annotated<C, A>
 where
-    A: Annotation<C>,
+    C: IndexMut<I>,
 {
-    fn from(elem: C) -> Self {
-        Self::new(elem)
+    fn index_mut(&mut self, index: I) -> &mut Self::Output {
+        // when indexing mutably, invalidate the annotation
+        self.invalidate();
+        &mut self.child[index]
+    }
+}
+
+impl<C, A> AsRef<C> for Annotated<C, A> {
+    fn as_ref(&self) -> &C {
+        &self.child
+    }
+}
+
+// comparisons and hashing delegate to the child, so borrowing it as a lookup
+// key is consistent
+impl<C, A> Borrow<C> for Annotated<C, A> {
+    fn borrow(&self) -> &C {
+        &self.child
     }
 }
 
@@ -236,6 +707,19 @@ where
 #[derive(Debug)]
 pub struct AnnotatedRefMut<'a, C, A> {
     annotated: &'a mut Annotated<C, A>,
+    recompute: Option<fn(&C) -> A>,
+}
+
+impl<'a, C, A> AnnotatedRefMut<'a, C, A> {
+    /// Consume the reference, setting the annotation over the child.
+    ///
+    /// This is useful when the new annotation is known after the mutation,
+    /// avoiding a re-computation from the child. The annotation is trusted to
+    /// be the correct one for the child.
+    pub fn commit_with(self, anno: A) {
+        *self.annotated.anno.get_mut() = Some(anno);
+        self.annotated.bump_generation();
+    }
 }
 
 impl<'a, C, A> Deref for AnnotatedRefMut<'a, C, A> {
@@ -249,70 +733,1278 @@ impl<'a, C, A> Deref for AnnotatedRefMut<'a, C, A> {
 impl<'a, C, A> DerefMut for AnnotatedRefMut<'a, C, A> {
     fn deref_mut(&mut self) -> &mut Self::Target {
         // when de-referencing mutably, invalidate the annotation
-        self.annotated.anno = RefCell::new(None);
+        self.annotated.invalidate();
 
         &mut self.annotated.child
     }
 }
 
+impl<'a, C, A> Drop for AnnotatedRefMut<'a, C, A> {
+    fn drop(&mut self) {
+        // if asked for, eagerly re-compute an invalidated annotation
+        if let Some(from_child) = self.recompute {
+            let anno = self.annotated.anno.get_mut();
+            if anno.is_none() {
+                *anno = Some(from_child(&self.annotated.child));
+            }
+        }
+    }
+}
+
 /// Annotation over a child.
-pub trait Annotation<C> {
+///
+/// # Composite annotations
+/// Several annotations can be carried by a single [`Annotated`] by using a
+/// tuple of them as the annotation. Since this trait is implemented for
+/// references and smart pointers to any child, it can't also be implemented
+/// for tuples over any child, and should instead be implemented for the
+/// tuple over the child in question:
+///
+/// ```
+/// use ranno::anno::{MaxKey, MinKey};
+/// use ranno::Annotation;
+///
+/// struct Keys(Vec<u32>);
+///
+/// impl Annotation<Keys> for MaxKey<u32> {
+///     fn from_child(keys: &Keys) -> Self {
+///         let max = keys.0.iter().max().copied();
+///         max.map_or(MaxKey::NegativeInfinity, MaxKey::Maximum)
+///     }
+/// }
+///
+/// impl Annotation<Keys> for MinKey<u32> {
+///     fn from_child(keys: &Keys) -> Self {
+///         let min = keys.0.iter().min().copied();
+///         min.map_or(MinKey::PositiveInfinity, MinKey::Minimum)
+///     }
+/// }
+///
+/// impl Annotation<Keys> for (MinKey<u32>, MaxKey<u32>) {
+///     fn from_child(keys: &Keys) -> Self {
+///         (MinKey::from_child(keys), MaxKey::from_child(keys))
+///     }
+/// }
+///
+/// let keys = Keys(vec![3, 7, 5]);
+/// let anno = <(MinKey<u32>, MaxKey<u32>)>::from_child(&keys);
+///
+/// assert_eq!(anno, (MinKey::Minimum(3), MaxKey::Maximum(7)));
+/// ```
+///
+/// Tuples of annotations implementing [`Combine`] implement it as well, by
+/// combining each element separately.
+///
+/// # Raw pointers
+/// Dereferencing a raw pointer, such as a [`NonNull`], is unsafe, so this
+/// trait is not implemented for them. Children behind a raw pointer can
+/// instead be annotated through a reference obtained from it, for as long as
+/// the pointer is known to be valid:
+///
+/// ```
+/// use core::ptr::NonNull;
+/// use ranno::anno::Sum;
+/// use ranno::{Annotated, Annotation};
+///
+/// struct Leaf(u64);
+///
+/// impl Annotation<Leaf> for Sum<u64> {
+///     fn from_child(leaf: &Leaf) -> Self {
+///         Sum(leaf.0)
+///     }
+/// }
+///
+/// let mut leaf = Leaf(42);
+/// let ptr = NonNull::from(&mut leaf);
+///
+/// // SAFETY: the pointer was just created from a valid reference
+/// let leaf = unsafe { ptr.as_ref() };
+/// let annotated = Annotated::<&Leaf, Sum<u64>>::new(leaf);
+///
+/// assert_eq!(*annotated.anno(), Sum(42));
+/// ```
+///
+/// [`NonNull`]: core::ptr::NonNull
+pub trait Annotation<C: ?Sized> {
     /// Compute the annotation from the child.
     fn from_child(t: &C) -> Self;
+
+    /// Returns true if the given annotation is the correct one for the
+    /// child.
+    ///
+    /// By default, the annotation is re-computed and compared with the given
+    /// one. Implementations may override this with a cheaper check.
+    fn verify(t: &C, anno: &Self) -> bool
+    where
+        Self: Sized + PartialEq,
+    {
+        Self::from_child(t) == *anno
+    }
+}
+
+/// Annotation that can be incrementally updated when its child changes.
+pub trait DeltaAnnotation<C>: Annotation<C> {
+    /// Description of a change made to the child.
+    type Delta;
+
+    /// Patch the annotation to reflect a change made to the child.
+    fn apply_delta(&mut self, delta: &Self::Delta);
+}
+
+/// Annotation that can be patched when single leaves are inserted into or
+/// removed from its child.
+pub trait IncrementalAnnotation<C, L>: Annotation<C> {
+    /// Patch the annotation to account for a leaf inserted into the child.
+    fn on_insert(&mut self, leaf: &L);
+
+    /// Patch the annotation to account for a leaf removed from the child.
+    fn on_remove(&mut self, leaf: &L);
 }
 
 impl<'a, C, A> Annotation<&'a C> for A
 where
+    C: ?Sized,
     A: Annotation<C>,
 {
     fn from_child(t: &&'a C) -> Self {
         A::from_child(t)
     }
+
+    fn verify(t: &&'a C, anno: &Self) -> bool
+    where
+        Self: PartialEq,
+    {
+        A::verify(t, anno)
+    }
 }
 
 impl<'a, C, A> Annotation<&'a mut C> for A
 where
+    C: ?Sized,
     A: Annotation<C>,
 {
     fn from_child(t: &&'a mut C) -> Self {
         A::from_child(t)
     }
+
+    fn verify(t: &&'a mut C, anno: &Self) -> bool
+    where
+        Self: PartialEq,
+    {
+        A::verify(t, anno)
+    }
 }
 
-#[cfg(feature = "alloc")]
-mod impl_alloc {
-    use super::Annotation;
+/// The annotation of an annotated child is the same as its own, re-using it
+/// if it was already computed.
+impl<C, A> Annotation<Annotated<C, A>> for A
+where
+    A: Annotation<C> + Clone,
+{
+    fn from_child(t: &Annotated<C, A>) -> Self {
+        t.anno_cloned()
+    }
 
-    extern crate alloc;
+    fn verify(t: &Annotated<C, A>, anno: &Self) -> bool
+    where
+        Self: PartialEq,
+    {
+        A::verify(&t.child, anno)
+    }
+}
 
-    use alloc::boxed::Box;
-    use alloc::rc::Rc;
-    use alloc::sync::Arc;
+/// An absent child is annotated with the default annotation.
+impl<C, A> Annotation<Option<C>> for A
+where
+    A: Annotation<C> + Default,
+{
+    fn from_child(t: &Option<C>) -> Self {
+        match t {
+            Some(t) => A::from_child(t),
+            None => A::default(),
+        }
+    }
 
-    impl<C, A> Annotation<Rc<C>> for A
+    fn verify(t: &Option<C>, anno: &Self) -> bool
     where
-        A: Annotation<C>,
+        Self: PartialEq,
     {
-        fn from_child(t: &Rc<C>) -> Self {
-            A::from_child(t.as_ref())
+        match t {
+            Some(t) => A::verify(t, anno),
+            None => *anno == A::default(),
         }
     }
+}
 
-    impl<C, A> Annotation<Arc<C>> for A
+/// The child is borrowed to compute the annotation.
+///
+/// Mutating the child through the cell doesn't invalidate the annotation of
+/// an [`Annotated`] holding it. Either mutate it through [`child_mut`], or
+/// call [`invalidate_shared`] after mutating it.
+///
+/// # Panics
+/// If the child is mutably borrowed while the annotation is computed.
+///
+/// [`child_mut`]: Annotated::child_mut
+/// [`invalidate_shared`]: Annotated::invalidate_shared
+impl<C, A> Annotation<RefCell<C>> for A
+where
+    C: ?Sized,
+    A: Annotation<C>,
+{
+    fn from_child(t: &RefCell<C>) -> Self {
+        A::from_child(&*t.borrow())
+    }
+
+    fn verify(t: &RefCell<C>, anno: &Self) -> bool
     where
-        A: Annotation<C>,
+        Self: PartialEq,
     {
-        fn from_child(t: &Arc<C>) -> Self {
-            A::from_child(t.as_ref())
-        }
+        A::verify(&*t.borrow(), anno)
     }
+}
 
-    impl<C, A> Annotation<Box<C>> for A
+/// The child is copied out of the cell to compute the annotation.
+///
+/// As with [`RefCell`], mutating the child through the cell doesn't
+/// invalidate the annotation of an [`Annotated`] holding it.
+impl<C, A> Annotation<Cell<C>> for A
+where
+    C: Copy,
+    A: Annotation<C>,
+{
+    fn from_child(t: &Cell<C>) -> Self {
+        A::from_child(&t.get())
+    }
+
+    fn verify(t: &Cell<C>, anno: &Self) -> bool
     where
-        A: Annotation<C>,
+        Self: PartialEq,
     {
-        fn from_child(t: &Box<C>) -> Self {
-            A::from_child(t.as_ref())
-        }
+        A::verify(&t.get(), anno)
+    }
+}
+
+impl<P, A> Annotation<Pin<P>> for A
+where
+    P: Deref,
+    A: Annotation<P::Target>,
+{
+    fn from_child(t: &Pin<P>) -> Self {
+        A::from_child(t.as_ref().get_ref())
+    }
+
+    fn verify(t: &Pin<P>, anno: &Self) -> bool
+    where
+        Self: PartialEq,
+    {
+        A::verify(t.as_ref().get_ref(), anno)
+    }
+}
+
+impl<C, A> Annotation<ManuallyDrop<C>> for A
+where
+    C: ?Sized,
+    A: Annotation<C>,
+{
+    fn from_child(t: &ManuallyDrop<C>) -> Self {
+        A::from_child(t)
+    }
+
+    fn verify(t: &ManuallyDrop<C>, anno: &Self) -> bool
+    where
+        Self: PartialEq,
+    {
+        A::verify(t, anno)
+    }
+}
+
+/// The annotations over each element of a slice are combined, in order.
+impl<C, A> Annotation<[C]> for A
+where
+    A: Annotation<C> + Combine,
+{
+    fn from_child(t: &[C]) -> Self {
+        t.iter()
+            .fold(A::identity(), |acc, c| acc.combine(&A::from_child(c)))
+    }
+}
+
+/// The annotations over each element of an array are combined, in order.
+impl<C, A, const N: usize> Annotation<[C; N]> for A
+where
+    A: Annotation<C> + Combine,
+{
+    fn from_child(t: &[C; N]) -> Self {
+        <A as Annotation<[C]>>::from_child(t)
+    }
+}
+
+#[cfg(feature = "alloc")]
+mod impl_alloc {
+    use super::{Annotated, Annotation, Combine};
+
+    use core::cell::RefCell;
+
+    extern crate alloc;
+
+    use alloc::borrow::{Cow, ToOwned};
+    use alloc::boxed::Box;
+    use alloc::collections::{BTreeMap, BTreeSet, LinkedList, VecDeque};
+    use alloc::rc::{self, Rc};
+    use alloc::sync::{self, Arc};
+    use alloc::vec::Vec;
+
+    impl<C, A> Annotation<Rc<C>> for A
+    where
+        C: ?Sized,
+        A: Annotation<C>,
+    {
+        fn from_child(t: &Rc<C>) -> Self {
+            A::from_child(t.as_ref())
+        }
+
+        fn verify(t: &Rc<C>, anno: &Self) -> bool
+        where
+            Self: PartialEq,
+        {
+            A::verify(t.as_ref(), anno)
+        }
+    }
+
+    impl<C, A> Annotation<Arc<C>> for A
+    where
+        C: ?Sized,
+        A: Annotation<C>,
+    {
+        fn from_child(t: &Arc<C>) -> Self {
+            A::from_child(t.as_ref())
+        }
+
+        fn verify(t: &Arc<C>, anno: &Self) -> bool
+        where
+            Self: PartialEq,
+        {
+            A::verify(t.as_ref(), anno)
+        }
+    }
+
+    impl<C, A> Annotation<Box<C>> for A
+    where
+        C: ?Sized,
+        A: Annotation<C>,
+    {
+        fn from_child(t: &Box<C>) -> Self {
+            A::from_child(t.as_ref())
+        }
+
+        fn verify(t: &Box<C>, anno: &Self) -> bool
+        where
+            Self: PartialEq,
+        {
+            A::verify(t.as_ref(), anno)
+        }
+    }
+
+    impl<'a, C, A> Annotation<Cow<'a, C>> for A
+    where
+        C: ?Sized + ToOwned,
+        A: Annotation<C>,
+    {
+        fn from_child(t: &Cow<'a, C>) -> Self {
+            A::from_child(t.as_ref())
+        }
+
+        fn verify(t: &Cow<'a, C>, anno: &Self) -> bool
+        where
+            Self: PartialEq,
+        {
+            A::verify(t.as_ref(), anno)
+        }
+    }
+
+    /// The annotations over each element of a vector are combined, in
+    /// order. Boxed slices are annotated in the same way, through the
+    /// implementations for boxes and slices.
+    impl<C, A> Annotation<Vec<C>> for A
+    where
+        A: Annotation<C> + Combine,
+    {
+        fn from_child(t: &Vec<C>) -> Self {
+            <A as Annotation<[C]>>::from_child(t)
+        }
+    }
+
+    /// The annotations over each element of a double-ended queue are
+    /// combined, in order.
+    impl<C, A> Annotation<VecDeque<C>> for A
+    where
+        A: Annotation<C> + Combine,
+    {
+        fn from_child(t: &VecDeque<C>) -> Self {
+            let (front, back) = t.as_slices();
+            let front = <A as Annotation<[C]>>::from_child(front);
+            let back = <A as Annotation<[C]>>::from_child(back);
+            front.combine(&back)
+        }
+    }
+
+    /// The annotations over each element of a linked list are combined, in
+    /// order.
+    impl<C, A> Annotation<LinkedList<C>> for A
+    where
+        A: Annotation<C> + Combine,
+    {
+        fn from_child(t: &LinkedList<C>) -> Self {
+            t.iter()
+                .fold(A::identity(), |acc, c| acc.combine(&A::from_child(c)))
+        }
+    }
+
+    /// The annotations over each value of a map are combined, in the order
+    /// of their keys.
+    impl<K, C, A> Annotation<BTreeMap<K, C>> for A
+    where
+        A: Annotation<C> + Combine,
+    {
+        fn from_child(t: &BTreeMap<K, C>) -> Self {
+            t.values()
+                .fold(A::identity(), |acc, c| acc.combine(&A::from_child(c)))
+        }
+    }
+
+    /// The annotations over each element of a set are combined, in order.
+    impl<C, A> Annotation<BTreeSet<C>> for A
+    where
+        A: Annotation<C> + Combine,
+    {
+        fn from_child(t: &BTreeSet<C>) -> Self {
+            t.iter()
+                .fold(A::identity(), |acc, c| acc.combine(&A::from_child(c)))
+        }
+    }
+
+    impl<C, A> Annotated<Rc<C>, A>
+    where
+        C: Clone,
+    {
+        /// Returns a mutable reference to the child, cloning it first if it is
+        /// shared.
+        ///
+        /// The annotation is invalidated.
+        pub fn make_child_mut(&mut self) -> &mut C {
+            self.invalidate();
+            Rc::make_mut(&mut self.child)
+        }
+    }
+
+    /// The child is upgraded to compute the annotation, with a dropped
+    /// child annotated with the default annotation.
+    ///
+    /// The annotation of an [`Annotated`] holding the reference is not
+    /// invalidated when the child is dropped, and should be invalidated
+    /// explicitly when that is relevant.
+    impl<C, A> Annotation<rc::Weak<C>> for A
+    where
+        A: Annotation<C> + Default,
+    {
+        fn from_child(t: &rc::Weak<C>) -> Self {
+            <A as Annotation<Option<Rc<C>>>>::from_child(&t.upgrade())
+        }
+    }
+
+    /// The child is upgraded to compute the annotation, with a dropped
+    /// child annotated with the default annotation.
+    ///
+    /// The annotation of an [`Annotated`] holding the reference is not
+    /// invalidated when the child is dropped, and should be invalidated
+    /// explicitly when that is relevant.
+    impl<C, A> Annotation<sync::Weak<C>> for A
+    where
+        A: Annotation<C> + Default,
+    {
+        fn from_child(t: &sync::Weak<C>) -> Self {
+            <A as Annotation<Option<Arc<C>>>>::from_child(&t.upgrade())
+        }
+    }
+
+    /// A child annotated with some metadata, held through a weak reference.
+    ///
+    /// This is useful for back-edges in structures, such as pointers to
+    /// parents, which would otherwise create reference cycles.
+    pub type WeakAnnotated<C, A> = Annotated<rc::Weak<C>, A>;
+
+    impl<C, A> Annotated<rc::Weak<C>, A>
+    where
+        A: Annotation<C> + Default,
+    {
+        /// Create a new annotation over a weak reference to the given child.
+        pub fn downgrade(child: &Rc<C>) -> Self {
+            Self::new(Rc::downgrade(child))
+        }
+    }
+
+    impl<C, A> Annotated<rc::Weak<C>, A> {
+        /// Attempt to upgrade the weak reference to the child, returning
+        /// `None` if it was dropped.
+        pub fn upgrade(&self) -> Option<Rc<C>> {
+            self.child.upgrade()
+        }
+    }
+
+    impl<C, A> Annotated<sync::Weak<C>, A>
+    where
+        A: Annotation<C> + Default,
+    {
+        /// Create a new annotation over a weak reference to the given child.
+        pub fn downgrade(child: &Arc<C>) -> Self {
+            Self::new(Arc::downgrade(child))
+        }
+    }
+
+    impl<C, A> Annotated<sync::Weak<C>, A> {
+        /// Attempt to upgrade the weak reference to the child, returning
+        /// `None` if it was dropped.
+        pub fn upgrade(&self) -> Option<Arc<C>> {
+            self.child.upgrade()
+        }
+    }
+
+    impl<C, A> Annotated<Rc<RefCell<C>>, A>
+    where
+        A: Annotation<C>,
+    {
+        /// Create a new annotation over a child shared behind an
+        /// `Rc<RefCell<C>>`.
+        pub fn new_shared(child: C) -> Self {
+            Self::new(Rc::new(RefCell::new(child)))
+        }
+    }
+
+    impl<C, A> Annotated<Rc<RefCell<C>>, A> {
+        /// Mutate the shared child using the given closure, invalidating the
+        /// annotation.
+        ///
+        /// # Panics
+        /// If the child is already borrowed, or a reference to the
+        /// annotation is still alive.
+        pub fn with_child_mut<R, F>(&self, f: F) -> R
+        where
+            F: FnOnce(&mut C) -> R,
+        {
+            let r = f(&mut self.child.borrow_mut());
+            self.invalidate_shared();
+            r
+        }
+    }
+
+    impl<C, A> Annotated<Arc<C>, A>
+    where
+        C: Clone,
+    {
+        /// Returns a mutable reference to the child, cloning it first if it is
+        /// shared.
+        ///
+        /// The annotation is invalidated.
+        pub fn make_child_mut(&mut self) -> &mut C {
+            self.invalidate();
+            Arc::make_mut(&mut self.child)
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+mod impl_std {
+    use super::{Annotated, Annotation};
+
+    use std::sync::{Arc, Mutex, PoisonError, RwLock};
+
+    /// The child is locked to compute the annotation.
+    ///
+    /// Mutating the child through the lock doesn't invalidate the annotation
+    /// of an [`Annotated`] holding it. Either mutate it through
+    /// [`child_mut`], or call [`invalidate_shared`] after mutating it.
+    ///
+    /// A panic while the child was locked leaves it poisoned, which is
+    /// ignored when computing the annotation.
+    ///
+    /// [`Annotated`]: crate::Annotated
+    /// [`child_mut`]: crate::Annotated::child_mut
+    /// [`invalidate_shared`]: crate::Annotated::invalidate_shared
+    impl<C, A> Annotation<Mutex<C>> for A
+    where
+        C: ?Sized,
+        A: Annotation<C>,
+    {
+        fn from_child(t: &Mutex<C>) -> Self {
+            let child = t.lock().unwrap_or_else(PoisonError::into_inner);
+            A::from_child(&*child)
+        }
+
+        fn verify(t: &Mutex<C>, anno: &Self) -> bool
+        where
+            Self: PartialEq,
+        {
+            let child = t.lock().unwrap_or_else(PoisonError::into_inner);
+            A::verify(&*child, anno)
+        }
+    }
+
+    /// The child is read-locked to compute the annotation.
+    ///
+    /// As with [`Mutex`], mutating the child through the lock doesn't
+    /// invalidate the annotation of an [`Annotated`] holding it, and
+    /// poisoning is ignored.
+    ///
+    /// [`Annotated`]: crate::Annotated
+    impl<C, A> Annotation<RwLock<C>> for A
+    where
+        C: ?Sized,
+        A: Annotation<C>,
+    {
+        fn from_child(t: &RwLock<C>) -> Self {
+            let child = t.read().unwrap_or_else(PoisonError::into_inner);
+            A::from_child(&*child)
+        }
+
+        fn verify(t: &RwLock<C>, anno: &Self) -> bool
+        where
+            Self: PartialEq,
+        {
+            let child = t.read().unwrap_or_else(PoisonError::into_inner);
+            A::verify(&*child, anno)
+        }
+    }
+
+    impl<C, A> Annotated<Arc<Mutex<C>>, A>
+    where
+        A: Annotation<C>,
+    {
+        /// Create a new annotation over a child shared behind an
+        /// `Arc<Mutex<C>>`.
+        pub fn new_shared_sync(child: C) -> Self {
+            Self::new(Arc::new(Mutex::new(child)))
+        }
+    }
+
+    impl<C, A> Annotated<Arc<Mutex<C>>, A> {
+        /// Mutate the shared child using the given closure, invalidating the
+        /// annotation.
+        ///
+        /// # Panics
+        /// If a reference to the annotation is still alive.
+        pub fn with_child_mut<R, F>(&self, f: F) -> R
+        where
+            F: FnOnce(&mut C) -> R,
+        {
+            let r = {
+                let mut child =
+                    self.child.lock().unwrap_or_else(PoisonError::into_inner);
+                f(&mut child)
+            };
+            self.invalidate_shared();
+            r
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use core::cell::Cell;
+    use std::panic::{self, AssertUnwindSafe};
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::anno::{Cardinality, Sum};
+
+    #[derive(Clone)]
+    struct Flaky {
+        value: u64,
+        fail: Cell<bool>,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Value(u64);
+
+    impl Annotation<Flaky> for Value {
+        fn from_child(flaky: &Flaky) -> Self {
+            if flaky.fail.get() {
+                panic!("failed computing annotation");
+            }
+            Value(flaky.value)
+        }
+    }
+
+    #[test]
+    fn anno_panic_leaves_annotated_usable() {
+        let annotated = Annotated::<_, Value>::new(Flaky {
+            value: 42,
+            fail: Cell::new(true),
+        });
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            annotated.anno();
+        }));
+        assert!(result.is_err());
+
+        assert!(!annotated.is_computed());
+        assert!(annotated.get_anno().is_none());
+
+        annotated.child().fail.set(false);
+        assert_eq!(*annotated.anno(), Value(42));
+        assert!(annotated.is_computed());
+    }
+
+    #[test]
+    fn child_mut_recompute_computes_on_drop() {
+        let mut annotated = Annotated::<_, Value>::new(Flaky {
+            value: 42,
+            fail: Cell::new(false),
+        });
+
+        annotated.child_mut_recompute().value = 7;
+        assert!(annotated.is_computed());
+        assert_eq!(annotated.get_anno().as_deref(), Some(&Value(7)));
+
+        // an annotation that wasn't invalidated isn't re-computed
+        annotated.child().fail.set(true);
+        let flaky = annotated.child_mut_recompute();
+        assert_eq!(flaky.value, 7);
+        drop(flaky);
+        assert_eq!(annotated.get_anno().as_deref(), Some(&Value(7)));
+    }
+
+    #[test]
+    fn set_anno_unchecked_skips_computation() {
+        let annotated = Annotated::<_, Value>::new(Flaky {
+            value: 42,
+            fail: Cell::new(true),
+        });
+        let generation = annotated.generation();
+
+        // SAFETY: no reference to the annotation is alive
+        unsafe { annotated.set_anno_unchecked(Value(42)) };
+
+        assert!(annotated.is_computed());
+        assert_ne!(annotated.generation(), generation);
+        assert_eq!(*annotated.anno(), Value(42));
+    }
+
+    #[derive(Default)]
+    struct Set(Vec<u64>);
+
+    impl Annotation<Set> for Cardinality {
+        fn from_child(set: &Set) -> Self {
+            Cardinality(set.0.len() as u64)
+        }
+    }
+
+    fn insert(set: &mut Set, key: u64) -> Option<()> {
+        (!set.0.contains(&key)).then(|| set.0.push(key))
+    }
+
+    #[test]
+    fn insert_with_patches_annotation() {
+        let mut annotated = Annotated::<_, Cardinality>::new(Set::default());
+        annotated.force();
+
+        assert_eq!(annotated.insert_with(1, insert), Some(()));
+        assert_eq!(annotated.insert_with(2, insert), Some(()));
+
+        assert!(annotated.is_computed());
+        assert_eq!(*annotated.anno(), Cardinality(2));
+        assert!(annotated.verify_cache());
+    }
+
+    #[test]
+    fn insert_with_duplicate_leaves_annotation_correct() {
+        let mut annotated =
+            Annotated::<_, Cardinality>::new(Set(Vec::from([1])));
+        annotated.force();
+
+        let generation = annotated.generation();
+        assert_eq!(annotated.insert_with(1, insert), None);
+
+        assert_ne!(annotated.generation(), generation);
+        assert_eq!(*annotated.anno(), Cardinality(1));
+        assert!(annotated.verify_cache());
+    }
+
+    #[test]
+    fn insert_with_panic_invalidates_annotation() {
+        let mut annotated = Annotated::<_, Cardinality>::new(Set::default());
+        annotated.force();
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            annotated.insert_with(1, |set, key| -> Option<()> {
+                set.0.push(key);
+                panic!("failed inserting leaf");
+            })
+        }));
+        assert!(result.is_err());
+
+        assert!(!annotated.is_computed());
+        assert_eq!(*annotated.anno(), Cardinality(1));
+    }
+
+    impl DeltaAnnotation<Set> for Cardinality {
+        type Delta = u64;
+
+        fn apply_delta(&mut self, added: &u64) {
+            self.0 += added;
+        }
+    }
+
+    #[test]
+    fn update_patches_annotation() {
+        let mut annotated = Annotated::<_, Cardinality>::new(Set::default());
+        annotated.force();
+        let generation = annotated.generation();
+
+        annotated.update(|set| {
+            set.0.extend([1, 2]);
+            2
+        });
+
+        assert!(annotated.is_computed());
+        assert_ne!(annotated.generation(), generation);
+        assert_eq!(*annotated.anno(), Cardinality(2));
+        assert!(annotated.verify_cache());
+    }
+
+    #[test]
+    fn update_panic_invalidates_annotation() {
+        let mut annotated = Annotated::<_, Cardinality>::new(Set::default());
+        annotated.force();
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            annotated.update(|set| {
+                set.0.push(1);
+                panic!("failed updating child");
+            })
+        }));
+        assert!(result.is_err());
+
+        assert!(!annotated.is_computed());
+        assert_eq!(*annotated.anno(), Cardinality(1));
+    }
+
+    #[test]
+    fn remove_with_panic_invalidates_annotation() {
+        let mut annotated =
+            Annotated::<_, Cardinality>::new(Set(Vec::from([1, 2])));
+        annotated.force();
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            annotated.remove_with(|set| -> Option<u64> {
+                set.0.pop();
+                panic!("failed removing leaf");
+            })
+        }));
+        assert!(result.is_err());
+
+        assert!(!annotated.is_computed());
+        assert_eq!(*annotated.anno(), Cardinality(1));
+    }
+
+    #[test]
+    fn remove_with_patches_annotation() {
+        let mut annotated =
+            Annotated::<_, Cardinality>::new(Set(Vec::from([1, 2])));
+        annotated.force();
+
+        assert_eq!(annotated.remove_with(|set| set.0.pop()), Some(2));
+        assert_eq!(annotated.remove_with(|_| None::<u64>), None);
+
+        assert!(annotated.is_computed());
+        assert_eq!(*annotated.anno(), Cardinality(1));
+        assert!(annotated.verify_cache());
+    }
+
+    #[test]
+    fn commit_with_sets_annotation() {
+        let mut annotated = Annotated::<_, Cardinality>::new(Set::default());
+        annotated.force();
+        let generation = annotated.generation();
+
+        let mut set = annotated.child_mut();
+        set.0.push(1);
+        set.commit_with(Cardinality(1));
+
+        assert!(annotated.is_computed());
+        assert_ne!(annotated.generation(), generation);
+        assert_eq!(*annotated.anno(), Cardinality(1));
+        assert!(annotated.verify_cache());
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    struct Leaf(u64);
+
+    impl Annotation<Leaf> for Sum<u64> {
+        fn from_child(leaf: &Leaf) -> Self {
+            Sum(leaf.0)
+        }
+    }
+
+    fn sum<C: ?Sized>(child: &C) -> Sum<u64>
+    where
+        Sum<u64>: Annotation<C>,
+    {
+        <Sum<u64> as Annotation<C>>::from_child(child)
+    }
+
+    #[test]
+    fn option_is_annotated_as_its_child() {
+        assert_eq!(sum(&Some(Leaf(3))), sum(&Leaf(3)));
+        assert_eq!(sum(&None::<Leaf>), Sum::default());
+
+        assert!(Annotated::<_, Sum<u64>>::new(Some(Leaf(3))).verify_cache());
+        assert!(<Sum<u64> as Annotation<Option<Leaf>>>::verify(
+            &None,
+            &Sum(0)
+        ));
+        assert!(!<Sum<u64> as Annotation<Option<Leaf>>>::verify(
+            &None,
+            &Sum(3)
+        ));
+    }
+
+    #[test]
+    fn array_combines_its_elements() {
+        let leaves = [Leaf(1), Leaf(2), Leaf(4)];
+        assert_eq!(sum(&leaves), Sum(7));
+        assert_eq!(sum(&leaves), sum(&leaves[..]));
+        assert_eq!(sum(&[Leaf(5)]), sum(&Leaf(5)));
+        assert_eq!(sum(&[Leaf(0); 0]), Sum::identity());
+    }
+
+    #[test]
+    fn slice_combines_its_elements() {
+        let leaves = [Leaf(1), Leaf(2), Leaf(4)];
+        assert_eq!(sum(&leaves[..]), Sum(7));
+        assert_eq!(sum(&leaves[1..2]), sum(&Leaf(2)));
+        assert_eq!(sum(&leaves[..0]), Sum::identity());
+
+        let annotated = Annotated::<&[Leaf], Sum<u64>>::new(&leaves[1..]);
+        assert_eq!(*annotated.anno(), Sum(6));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn vec_combines_its_elements() {
+        use std::boxed::Box;
+
+        let leaves = Vec::from([Leaf(1), Leaf(2), Leaf(4)]);
+        assert_eq!(sum(&leaves), sum(&leaves[..]));
+        assert_eq!(sum(&Vec::<Leaf>::new()), Sum::identity());
+
+        let boxed: Box<[Leaf]> = leaves.into_boxed_slice();
+        assert_eq!(sum(&boxed), Sum(7));
+        assert_eq!(sum(&Box::<[Leaf]>::default()), Sum::identity());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn vec_deque_and_linked_list_combine_their_elements() {
+        use std::collections::{LinkedList, VecDeque};
+
+        // pushing to the front wraps around, splitting the queue in two
+        let mut queue = VecDeque::with_capacity(4);
+        queue.extend([Leaf(1), Leaf(2)]);
+        queue.push_front(Leaf(4));
+        assert!(!queue.as_slices().1.is_empty());
+
+        assert_eq!(sum(&queue), Sum(7));
+        assert_eq!(sum(&VecDeque::<Leaf>::new()), Sum::identity());
+
+        let list = LinkedList::from([Leaf(1), Leaf(2), Leaf(4)]);
+        assert_eq!(sum(&list), Sum(7));
+        assert_eq!(sum(&LinkedList::<Leaf>::new()), Sum::identity());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn btree_collections_combine_their_elements() {
+        use std::collections::{BTreeMap, BTreeSet};
+
+        let map = BTreeMap::from([("b", Leaf(2)), ("a", Leaf(1))]);
+        assert_eq!(sum(&map), Sum(3));
+        assert_eq!(sum(&BTreeMap::<u8, Leaf>::new()), Sum::identity());
+
+        let set = BTreeSet::from([Leaf(4), Leaf(1), Leaf(4)]);
+        assert_eq!(sum(&set), Sum(5));
+        assert_eq!(sum(&BTreeSet::<Leaf>::new()), Sum::identity());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn cow_is_annotated_as_its_child() {
+        use std::borrow::Cow;
+
+        let leaf = Leaf(3);
+        assert_eq!(sum(&Cow::Borrowed(&leaf)), sum(&leaf));
+        assert_eq!(sum(&Cow::<Leaf>::Owned(leaf)), sum(&leaf));
+
+        let leaves = [Leaf(1), Leaf(2)];
+        assert_eq!(sum(&Cow::Borrowed(&leaves[..])), Sum(3));
+        assert_eq!(sum(&Cow::<[Leaf]>::Owned(Vec::new())), Sum::identity());
+    }
+
+    #[test]
+    fn cells_are_annotated_as_their_child() {
+        assert_eq!(sum(&RefCell::new(Leaf(3))), sum(&Leaf(3)));
+        assert_eq!(sum(&Cell::new(Leaf(3))), sum(&Leaf(3)));
+
+        let leaves = RefCell::new([Leaf(1), Leaf(2)]);
+        assert_eq!(sum(&leaves), Sum(3));
+
+        // mutating through the cell needs explicit invalidation
+        let annotated = Annotated::<_, Sum<u64>>::new(Cell::new(Leaf(3)));
+        annotated.force();
+        annotated.child().set(Leaf(5));
+        assert!(!annotated.verify_cache());
+
+        annotated.invalidate_shared();
+        assert_eq!(*annotated.anno(), Sum(5));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn locks_are_annotated_as_their_child() {
+        use std::sync::{Mutex, RwLock};
+
+        let mutex = Mutex::new(Leaf(3));
+        let rw_lock = RwLock::new(Leaf(3));
+        assert_eq!(sum(&mutex), sum(&Leaf(3)));
+        assert_eq!(sum(&rw_lock), sum(&Leaf(3)));
+
+        assert_eq!(sum(&Mutex::new(Vec::<Leaf>::new())), Sum::identity());
+        assert_eq!(sum(&RwLock::new(Vec::<Leaf>::new())), Sum::identity());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn poisoned_locks_are_annotated_as_their_child() {
+        use std::sync::{Mutex, RwLock};
+
+        let mutex = Mutex::new(Leaf(3));
+        let rw_lock = RwLock::new(Leaf(3));
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let _guard = mutex.lock().unwrap();
+            *rw_lock.write().unwrap() = Leaf(5);
+            let _guard = rw_lock.write().unwrap();
+            panic!("failed while holding the locks");
+        }));
+        assert!(result.is_err());
+        assert!(mutex.is_poisoned());
+        assert!(rw_lock.is_poisoned());
+
+        assert_eq!(sum(&mutex), Sum(3));
+        assert_eq!(sum(&rw_lock), Sum(5));
+
+        let annotated = Annotated::<_, Sum<u64>>::new(mutex);
+        assert!(annotated.verify_cache());
+        let annotated = Annotated::<_, Sum<u64>>::new(rw_lock);
+        assert!(annotated.verify_cache());
+    }
+
+    #[test]
+    fn pin_and_manually_drop_are_annotated_as_their_child() {
+        let leaf = Leaf(3);
+        assert_eq!(sum(&Pin::new(&leaf)), sum(&leaf));
+        assert_eq!(sum(&ManuallyDrop::new(leaf)), sum(&leaf));
+
+        let leaves = [Leaf(1), Leaf(2)];
+        assert_eq!(sum(&Pin::new(&leaves)), Sum(3));
+        assert_eq!(sum(&ManuallyDrop::new([Leaf(0); 0])), Sum::identity());
+
+        let annotated = Annotated::<_, Sum<u64>>::new(ManuallyDrop::new(leaf));
+        assert!(annotated.verify_cache());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn with_child_mut_invalidates_shared_child() {
+        let annotated = Annotated::<_, Sum<u64>>::new_shared(Leaf(3));
+        assert_eq!(*annotated.anno(), Sum(3));
+        let generation = annotated.generation();
+
+        let old = annotated.with_child_mut(|leaf| mem::replace(leaf, Leaf(5)));
+        assert_eq!(old, Leaf(3));
+
+        assert!(!annotated.is_computed());
+        assert_ne!(annotated.generation(), generation);
+        assert_eq!(*annotated.anno(), Sum(5));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn with_child_mut_invalidates_shared_sync_child() {
+        let annotated = Annotated::<_, Sum<u64>>::new_shared_sync(Leaf(3));
+        assert_eq!(*annotated.anno(), Sum(3));
+        let generation = annotated.generation();
+
+        let old = annotated.with_child_mut(|leaf| mem::replace(leaf, Leaf(5)));
+        assert_eq!(old, Leaf(3));
+
+        assert!(!annotated.is_computed());
+        assert_ne!(annotated.generation(), generation);
+        assert_eq!(*annotated.anno(), Sum(5));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn smart_pointers_are_annotated_as_their_child() {
+        use std::boxed::Box;
+        use std::rc::Rc;
+        use std::sync::Arc;
+
+        assert_eq!(sum(&Rc::new(Leaf(3))), sum(&Leaf(3)));
+        assert_eq!(sum(&Arc::new(Leaf(3))), sum(&Leaf(3)));
+        assert_eq!(sum(&Box::new(Leaf(3))), sum(&Leaf(3)));
+
+        let leaves: Rc<[Leaf]> = Rc::from([Leaf(1), Leaf(2)]);
+        assert_eq!(sum(&leaves), Sum(3));
+        let leaves: Arc<[Leaf]> = Arc::from([]);
+        assert_eq!(sum(&leaves), Sum::identity());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn weak_is_annotated_as_its_live_child() {
+        use std::rc::Rc;
+        use std::sync::{self, Arc};
+
+        let leaf = Rc::new(Leaf(3));
+        let weak = WeakAnnotated::<_, Sum<u64>>::downgrade(&leaf);
+        assert_eq!(weak.upgrade(), Some(leaf.clone()));
+        assert_eq!(*weak.anno(), sum(&Leaf(3)));
+
+        // dropping the child doesn't invalidate the annotation
+        drop(leaf);
+        assert_eq!(weak.upgrade(), None);
+        assert_eq!(*weak.anno(), Sum(3));
+        assert!(!weak.verify_cache());
+
+        let leaf = Arc::new(Leaf(3));
+        let weak = Annotated::<sync::Weak<_>, Sum<u64>>::downgrade(&leaf);
+        assert_eq!(weak.upgrade(), Some(leaf.clone()));
+        assert_eq!(*weak.anno(), sum(&Leaf(3)));
+
+        drop(leaf);
+        assert_eq!(weak.upgrade(), None);
+        assert!(!weak.verify_cache());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn dead_weak_is_annotated_with_default() {
+        use std::rc::{self, Rc};
+        use std::sync::{self, Arc};
+
+        let mut weak =
+            WeakAnnotated::<_, Sum<u64>>::downgrade(&Rc::new(Leaf(3)));
+        assert_eq!(*weak.anno(), Sum::default());
+        weak.invalidate();
+        assert_eq!(*weak.anno(), Sum::default());
+
+        let weak =
+            Annotated::<sync::Weak<_>, Sum<u64>>::downgrade(&Arc::new(Leaf(3)));
+        assert_eq!(*weak.anno(), Sum::default());
+
+        assert_eq!(sum(&rc::Weak::<Leaf>::new()), Sum::default());
+        assert_eq!(sum(&sync::Weak::<Leaf>::new()), Sum::default());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn make_child_mut_clones_shared_child() {
+        use std::rc::Rc;
+
+        let shared = Rc::new(Leaf(3));
+        let mut annotated = Annotated::<_, Sum<u64>>::new(shared.clone());
+        annotated.force();
+        let generation = annotated.generation();
+
+        *annotated.make_child_mut() = Leaf(5);
+
+        assert!(!annotated.is_computed());
+        assert_ne!(annotated.generation(), generation);
+        assert_eq!(*annotated.anno(), Sum(5));
+
+        // the other handle still sees the original child
+        assert_eq!(*shared, Leaf(3));
+        assert_eq!(Rc::strong_count(&shared), 1);
+
+        // a child that isn't shared is mutated in place
+        let ptr = Rc::as_ptr(annotated.child());
+        *annotated.make_child_mut() = Leaf(7);
+        assert_eq!(Rc::as_ptr(annotated.child()), ptr);
+        assert_eq!(*annotated.anno(), Sum(7));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn make_child_mut_clones_shared_sync_child() {
+        use std::sync::Arc;
+
+        let shared = Arc::new(Leaf(3));
+        let mut annotated = Annotated::<_, Sum<u64>>::new(shared.clone());
+        annotated.force();
+
+        *annotated.make_child_mut() = Leaf(5);
+
+        assert!(!annotated.is_computed());
+        assert_eq!(*annotated.anno(), Sum(5));
+        assert_eq!(*shared, Leaf(3));
+        assert_eq!(Arc::strong_count(&shared), 1);
+    }
+
+    #[test]
+    fn map_child_discards_annotation() {
+        let annotated = Annotated::<_, Sum<u64>>::new(Leaf(3));
+        annotated.force();
+        let generation = annotated.generation();
+
+        let mapped = annotated.map_child(|leaf| Leaf(leaf.0 + 1));
+
+        assert!(!mapped.is_computed());
+        assert_ne!(mapped.generation(), generation);
+        assert_eq!(*mapped.anno(), Sum(4));
+    }
+
+    #[test]
+    fn child_and_anno_computes_annotation() {
+        let annotated = Annotated::<_, Sum<u64>>::new(Leaf(3));
+
+        let (leaf, anno) = annotated.child_and_anno();
+        assert_eq!(*leaf, Leaf(3));
+        assert_eq!(*anno, Sum(3));
+        drop(anno);
+
+        assert!(annotated.is_computed());
+    }
+
+    #[test]
+    fn clone_keeps_computed_annotation() {
+        let annotated = Annotated::<_, Value>::new(Flaky {
+            value: 42,
+            fail: Cell::new(false),
+        });
+        annotated.force();
+        annotated.child().fail.set(true);
+
+        let cloned = annotated.clone();
+        assert!(cloned.is_computed());
+        assert_eq!(cloned.generation(), annotated.generation());
+        assert_eq!(*cloned.anno(), Value(42));
+
+        // an uncomputed annotation stays uncomputed
+        let mut annotated = annotated;
+        annotated.invalidate();
+        assert!(!annotated.clone().is_computed());
     }
 }
@@ -0,0 +1,178 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use core::mem;
+use core::ops::Deref;
+
+use std::sync::{PoisonError, RwLock, RwLockReadGuard};
+
+use crate::Annotation;
+
+/// A child annotated with some metadata, using a lock to share it between
+/// threads.
+///
+/// This exposes the same API as [`Annotated`], with the annotation lazily
+/// computed the first time it is asked for using [`anno`]. Threads asking
+/// for it while it is being computed block until it is ready.
+///
+/// [`Annotated`]: crate::Annotated
+/// [`anno`]: LockedAnnotated::anno
+#[derive(Debug)]
+pub struct LockedAnnotated<C, A> {
+    child: C,
+    anno: RwLock<Option<A>>,
+}
+
+impl<C, A> LockedAnnotated<C, A> {
+    /// Returns the annotated child.
+    pub fn child(&self) -> &C {
+        &self.child
+    }
+
+    /// Returns the annotation over the child, if it was already computed.
+    pub fn get_anno(&self) -> Option<LockedAnnoRef<'_, A>> {
+        let guard = self.read_anno();
+        guard.is_some().then_some(LockedAnnoRef { guard })
+    }
+
+    /// Returns true if the annotation over the child is already computed.
+    pub fn is_computed(&self) -> bool {
+        self.read_anno().is_some()
+    }
+
+    /// Invalidate the annotation, forcing it to be re-computed the next time
+    /// it is asked for.
+    pub fn invalidate(&mut self) {
+        *self.anno_mut() = None;
+    }
+
+    /// Replace the child with a new one, returning the old child.
+    ///
+    /// The annotation is invalidated.
+    pub fn replace_child(&mut self, child: C) -> C {
+        self.invalidate();
+        mem::replace(&mut self.child, child)
+    }
+
+    /// Consume the structure and return the child and the annotation, if it
+    /// was already computed.
+    pub fn split(self) -> (C, Option<A>) {
+        let anno = self
+            .anno
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner);
+        (self.child, anno)
+    }
+
+    /// Consume the structure and return the child, discarding the annotation.
+    pub fn into_child(self) -> C {
+        self.child
+    }
+
+    // A panic while computing the annotation poisons the lock while leaving
+    // it empty, so it is safe to ignore poisoning.
+
+    fn read_anno(&self) -> RwLockReadGuard<'_, Option<A>> {
+        self.anno.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn anno_mut(&mut self) -> &mut Option<A> {
+        self.anno.get_mut().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<C, A> LockedAnnotated<C, A>
+where
+    A: Annotation<C>,
+{
+    /// Create a new annotation over a child.
+    pub fn new(child: C) -> Self {
+        Self {
+            anno: RwLock::new(None),
+            child,
+        }
+    }
+
+    /// Create a new annotated child with an already computed annotation.
+    ///
+    /// The annotation is trusted to be the correct one for the child, and
+    /// will not be recomputed until the child is mutated.
+    pub fn with_anno(child: C, anno: A) -> Self {
+        Self {
+            anno: RwLock::new(Some(anno)),
+            child,
+        }
+    }
+
+    /// Returns the annotation over the child.
+    pub fn anno(&self) -> LockedAnnoRef<'_, A> {
+        if let Some(anno) = self.get_anno() {
+            return anno;
+        }
+
+        {
+            let mut anno =
+                self.anno.write().unwrap_or_else(PoisonError::into_inner);
+
+            // another thread might have computed the annotation in the
+            // meantime
+            if anno.is_none() {
+                *anno = Some(A::from_child(&self.child));
+            }
+        }
+
+        // the annotation can only be invalidated through a mutable reference,
+        // so it is guaranteed to still be computed
+        LockedAnnoRef {
+            guard: self.read_anno(),
+        }
+    }
+
+    /// Returns a mutable reference to the annotated child.
+    pub fn child_mut(&mut self) -> LockedAnnotatedRefMut<'_, C, A> {
+        LockedAnnotatedRefMut { annotated: self }
+    }
+}
+
+impl<C, A> Clone for LockedAnnotated<C, A>
+where
+    C: Clone,
+    A: Clone,
+{
+    fn clone(&self) -> Self {
+        // the annotation is cloned as well, to avoid re-computing it
+        Self {
+            child: self.child.clone(),
+            anno: RwLock::new(self.read_anno().clone()),
+        }
+    }
+}
+
+crate::macros::impl_by_child!(LockedAnnotated<C, A>);
+crate::macros::impl_from_child!(LockedAnnotated<C, A>, Annotation);
+
+/// A reference to the annotation of a [`LockedAnnotated`].
+///
+/// The annotation is read-locked for as long as the reference is alive.
+#[derive(Debug)]
+pub struct LockedAnnoRef<'a, A> {
+    guard: RwLockReadGuard<'a, Option<A>>,
+}
+
+impl<'a, A> Deref for LockedAnnoRef<'a, A> {
+    type Target = A;
+
+    fn deref(&self) -> &Self::Target {
+        // unwrapping is ok since references are only created when the
+        // annotation is computed
+        self.guard.as_ref().unwrap()
+    }
+}
+
+crate::macros::child_ref_mut! {
+    /// A mutable reference to a locked annotated child.
+    LockedAnnotatedRefMut => LockedAnnotated<C, A>
+}
@@ -0,0 +1,784 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+/// Implement [`Annotation`] over a child for tuples of up to eight
+/// annotations over the same child.
+///
+/// The annotation over the child is computed by computing each element of
+/// the tuple over the child. Since [`Annotation`] can't be implemented for
+/// tuples over any child, this should be invoked by the crate defining the
+/// child. Generic parameters of the child are given in angle brackets before
+/// it.
+///
+/// # Example
+/// ```
+/// use ranno::anno::{Cardinality, Sum};
+/// use ranno::Annotation;
+///
+/// struct Leaf<T>(T);
+///
+/// impl<T> Annotation<Leaf<T>> for Cardinality {
+///     fn from_child(_: &Leaf<T>) -> Self {
+///         Cardinality::LEAF
+///     }
+/// }
+///
+/// impl<T: Copy> Annotation<Leaf<T>> for Sum<T> {
+///     fn from_child(leaf: &Leaf<T>) -> Self {
+///         Sum(leaf.0)
+///     }
+/// }
+///
+/// ranno::impl_annotation_tuples!(<T> Leaf<T>);
+///
+/// let anno = <(Cardinality, Sum<u64>)>::from_child(&Leaf(42));
+/// assert_eq!(anno, (Cardinality(1), Sum(42)));
+/// ```
+///
+/// [`Annotation`]: crate::Annotation
+#[macro_export]
+macro_rules! impl_annotation_tuples {
+    (@impl [$($gen:ident),*] $child:ty; $($anno:ident),+) => {
+        impl<$($gen,)* $($anno),+> $crate::Annotation<$child> for ($($anno,)+)
+        where
+            $($anno: $crate::Annotation<$child>,)+
+        {
+            fn from_child(t: &$child) -> Self {
+                ($(<$anno as $crate::Annotation<$child>>::from_child(t),)+)
+            }
+        }
+    };
+    (<$($gen:ident),*> $child:ty) => {
+        $crate::impl_annotation_tuples!(@impl [$($gen),*] $child; A0);
+        $crate::impl_annotation_tuples!(@impl [$($gen),*] $child; A0, A1);
+        $crate::impl_annotation_tuples!(@impl [$($gen),*] $child; A0, A1, A2);
+        $crate::impl_annotation_tuples!(
+            @impl [$($gen),*] $child; A0, A1, A2, A3
+        );
+        $crate::impl_annotation_tuples!(
+            @impl [$($gen),*] $child; A0, A1, A2, A3, A4
+        );
+        $crate::impl_annotation_tuples!(
+            @impl [$($gen),*] $child; A0, A1, A2, A3, A4, A5
+        );
+        $crate::impl_annotation_tuples!(
+            @impl [$($gen),*] $child; A0, A1, A2, A3, A4, A5, A6
+        );
+        $crate::impl_annotation_tuples!(
+            @impl [$($gen),*] $child; A0, A1, A2, A3, A4, A5, A6, A7
+        );
+    };
+    ($child:ty) => {
+        $crate::impl_annotation_tuples!(<> $child);
+    };
+}
+
+/// Define a newtype annotation over a number.
+///
+/// The newtype implements [`Combine`] using the given operation - one of
+/// `add`, `max`, or `min` - together with `From` conversions to and from the
+/// number, and addition and subtraction. Its `Default` is the identity of the
+/// operation, such that an absent child, annotated with the default, doesn't
+/// affect the combined annotation. Attributes given before the name,
+/// including doc comments, are applied to the newtype.
+///
+/// Optionally, [`Annotation`] can be implemented over leaf types by giving a
+/// closure extracting the number from a leaf.
+///
+/// # Example
+/// ```
+/// use ranno::{numeric_annotation, Annotation, Combine};
+///
+/// struct Item {
+///     weight: u64,
+/// }
+///
+/// numeric_annotation!(
+///     /// The total weight of the items in a structure.
+///     #[derive(Eq, Hash)]
+///     pub Weight: u64,
+///     combine = add,
+///     leaf Item => |item| item.weight,
+/// );
+///
+/// let a = Weight::from_child(&Item { weight: 3 });
+/// let b = Weight::from_child(&Item { weight: 4 });
+///
+/// assert_eq!(a.combine(&b), Weight(7));
+/// assert_eq!(Weight::identity(), Weight::default());
+/// assert_eq!(u64::from(a + b - a), 4);
+///
+/// struct Reading(i64);
+///
+/// numeric_annotation!(
+///     pub Lowest: i64,
+///     combine = min,
+///     leaf Reading => |r| r.0,
+/// );
+/// numeric_annotation!(
+///     pub Highest: i64,
+///     combine = max,
+///     leaf Reading => |r| r.0,
+/// );
+///
+/// assert_eq!(Lowest::default(), Lowest(i64::MAX));
+/// assert_eq!(Highest::default(), Highest(i64::MIN));
+///
+/// // an absent child doesn't affect the extrema of its siblings
+/// let readings = [Some(Reading(3)), None, Some(Reading(7))];
+/// assert_eq!(Lowest::from_child(&readings), Lowest(3));
+///
+/// let readings = [Some(Reading(-3)), None, Some(Reading(-7))];
+/// assert_eq!(Highest::from_child(&readings), Highest(-3));
+///
+/// let none = [None::<Reading>, None];
+/// assert_eq!(Lowest::from_child(&none), Lowest::identity());
+/// ```
+///
+/// [`Combine`]: crate::Combine
+/// [`Annotation`]: crate::Annotation
+#[macro_export]
+macro_rules! numeric_annotation {
+    (@combine add, $ty:ty, $a:expr, $b:expr) => {
+        $a + $b
+    };
+    (@combine max, $ty:ty, $a:expr, $b:expr) => {
+        if $a >= $b { $a } else { $b }
+    };
+    (@combine min, $ty:ty, $a:expr, $b:expr) => {
+        if $a <= $b { $a } else { $b }
+    };
+    (@identity add, $ty:ty) => {
+        <$ty as ::core::default::Default>::default()
+    };
+    (@identity max, $ty:ty) => {
+        <$ty>::MIN
+    };
+    (@identity min, $ty:ty) => {
+        <$ty>::MAX
+    };
+    (
+        $(#[$meta:meta])*
+        $vis:vis $name:ident: $ty:ty,
+        combine = $op:ident
+        $(, leaf $leaf:ty => |$arg:ident| $body:expr)*
+        $(,)?
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+        $vis struct $name(pub $ty);
+
+        impl ::core::default::Default for $name {
+            fn default() -> Self {
+                Self($crate::numeric_annotation!(@identity $op, $ty))
+            }
+        }
+
+        impl $crate::Combine for $name {
+            fn identity() -> Self {
+                Self($crate::numeric_annotation!(@identity $op, $ty))
+            }
+
+            fn combine(&self, other: &Self) -> Self {
+                Self($crate::numeric_annotation!(
+                    @combine $op, $ty, self.0, other.0
+                ))
+            }
+        }
+
+        impl ::core::convert::From<$ty> for $name {
+            fn from(n: $ty) -> Self {
+                Self(n)
+            }
+        }
+
+        impl ::core::convert::From<$name> for $ty {
+            fn from(anno: $name) -> Self {
+                anno.0
+            }
+        }
+
+        impl ::core::ops::Add for $name {
+            type Output = Self;
+
+            fn add(self, other: Self) -> Self {
+                Self(self.0 + other.0)
+            }
+        }
+
+        impl ::core::ops::AddAssign for $name {
+            fn add_assign(&mut self, other: Self) {
+                self.0 += other.0;
+            }
+        }
+
+        impl ::core::ops::Sub for $name {
+            type Output = Self;
+
+            fn sub(self, other: Self) -> Self {
+                Self(self.0 - other.0)
+            }
+        }
+
+        impl ::core::ops::SubAssign for $name {
+            fn sub_assign(&mut self, other: Self) {
+                self.0 -= other.0;
+            }
+        }
+
+        $(
+            impl $crate::Annotation<$leaf> for $name {
+                fn from_child($arg: &$leaf) -> Self {
+                    Self($body)
+                }
+            }
+        )*
+    };
+}
+
+/// Generate tests checking that an annotation over a child obeys the laws
+/// expected by this crate.
+///
+/// The generated tests use the trees yielded by the given expression, which
+/// is evaluated multiple times and should yield the same trees each time. They
+/// check that:
+///
+/// - the annotation cached by an [`Annotated`] is the same as a freshly
+///   computed one, after its child is replaced by each of the other trees.
+/// - [`Combine`] is associative over the annotations of any three trees.
+/// - [`Combine::identity`] leaves the annotation of any tree unchanged.
+///
+/// The annotation must implement `Debug`, `PartialEq`, and [`Combine`]. The
+/// tests are generated as functions with fixed names, so the macro should be
+/// invoked at most once per module.
+///
+/// # Mutations
+/// Optionally, the cached annotation can be checked against a freshly
+/// computed one after mutating each tree, repeatedly, in the following ways:
+///
+/// - `mutate = |tree| ...` mutates a tree through [`child_mut`],
+///   [`child_mut_recompute`], and through the [`child_mut`] of an
+///   [`Annotated`] nested in another. The annotation must also implement
+///   `Clone`.
+/// - `update = |tree| ...` mutates a tree through [`update`], returning the
+///   delta of the change.
+/// - `insert = leaves => |tree, leaf| ...` inserts each of the given leaves
+///   into a tree through [`insert_with`], twice, returning `None` if the
+///   leaf wasn't inserted.
+///
+/// # Example
+/// ```
+/// # fn main() {}
+/// use ranno::anno::Sum;
+/// use ranno::{combine, Annotated, Annotation};
+///
+/// struct Leaf(u64);
+///
+/// impl Annotation<Leaf> for Sum<u64> {
+///     fn from_child(leaf: &Leaf) -> Self {
+///         Sum(leaf.0)
+///     }
+/// }
+///
+/// struct Node(Vec<Annotated<Leaf, Sum<u64>>>);
+///
+/// impl Annotation<Node> for Sum<u64> {
+///     fn from_child(node: &Node) -> Self {
+///         combine::combine_children(&node.0)
+///     }
+/// }
+///
+/// #[cfg(test)]
+/// mod tests {
+///     use super::*;
+///
+///     ranno::annotation_laws!(
+///         Sum<u64>,
+///         Node,
+///         (0..4).map(|n| Node(
+///             (0..n).map(|k| Annotated::new(Leaf(k))).collect()
+///         )),
+///         mutate = |node| node.0.push(Annotated::new(Leaf(7))),
+///         insert = [3, 5] => |node, leaf| {
+///             node.0.push(Annotated::new(Leaf(leaf)));
+///             Some(())
+///         },
+///     );
+/// }
+/// ```
+///
+/// [`Annotated`]: crate::Annotated
+/// [`Combine`]: crate::Combine
+/// [`Combine::identity`]: crate::Combine::identity
+/// [`child_mut`]: crate::Annotated::child_mut
+/// [`child_mut_recompute`]: crate::Annotated::child_mut_recompute
+/// [`update`]: crate::Annotated::update
+/// [`insert_with`]: crate::Annotated::insert_with
+#[macro_export]
+macro_rules! annotation_laws {
+    (
+        $anno:ty, $child:ty, $trees:expr
+        $(, mutate = $mutate:expr)?
+        $(, update = $update:expr)?
+        $(, insert = $leaves:expr => $insert:expr)?
+        $(,)?
+    ) => {
+        #[test]
+        fn annotation_laws_cached_matches_fresh() {
+            for tree in $trees {
+                let mut annotated =
+                    $crate::Annotated::<$child, $anno>::new(tree);
+                assert_eq!(
+                    *annotated.anno(),
+                    <$anno as $crate::Annotation<$child>>::from_child(
+                        annotated.child()
+                    ),
+                );
+
+                for other in $trees {
+                    annotated.replace_child(other);
+                    let fresh =
+                        <$anno as $crate::Annotation<$child>>::from_child(
+                            annotated.child(),
+                        );
+                    assert_eq!(*annotated.anno(), fresh);
+                    assert!(annotated.verify_cache());
+                }
+            }
+        }
+
+        #[test]
+        fn annotation_laws_combine_is_associative() {
+            use $crate::{Annotation, Combine};
+
+            for a in $trees {
+                let a = <$anno as Annotation<$child>>::from_child(&a);
+                for b in $trees {
+                    let b = <$anno as Annotation<$child>>::from_child(&b);
+                    for c in $trees {
+                        let c = <$anno as Annotation<$child>>::from_child(&c);
+                        assert_eq!(
+                            a.combine(&b).combine(&c),
+                            a.combine(&b.combine(&c)),
+                        );
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn annotation_laws_combine_has_identity() {
+            use $crate::{Annotation, Combine};
+
+            for tree in $trees {
+                let anno = <$anno as Annotation<$child>>::from_child(&tree);
+                let identity = <$anno as Combine>::identity();
+                assert_eq!(identity.combine(&anno), anno);
+                assert_eq!(anno.combine(&identity), anno);
+            }
+        }
+
+        $(
+            #[test]
+            fn annotation_laws_cached_matches_fresh_after_mutation() {
+                use $crate::{Annotated, Annotation};
+
+                // gives the closure its signature before its body is checked
+                fn mutation<F: FnMut(&mut $child)>(f: F) -> F {
+                    f
+                }
+                let mut mutate = mutation($mutate);
+
+                for tree in $trees {
+                    let mut annotated = Annotated::<$child, $anno>::new(tree);
+                    for _ in 0..3 {
+                        annotated.force();
+                        mutate(&mut annotated.child_mut());
+
+                        let fresh = <$anno as Annotation<$child>>::from_child(
+                            annotated.child(),
+                        );
+                        assert_eq!(*annotated.anno(), fresh);
+                    }
+
+                    for _ in 0..3 {
+                        mutate(&mut annotated.child_mut_recompute());
+                        assert!(annotated.is_computed());
+
+                        let fresh = <$anno as Annotation<$child>>::from_child(
+                            annotated.child(),
+                        );
+                        assert_eq!(*annotated.anno(), fresh);
+                    }
+
+                    let mut nested =
+                        Annotated::<Annotated<$child, $anno>, $anno>::new(
+                            annotated,
+                        );
+                    for _ in 0..3 {
+                        nested.force();
+                        mutate(&mut nested.child_mut().child_mut());
+
+                        let fresh = <$anno as Annotation<$child>>::from_child(
+                            nested.child().child(),
+                        );
+                        assert_eq!(*nested.anno(), fresh);
+                        assert_eq!(*nested.child().anno(), fresh);
+                    }
+                }
+            }
+        )?
+
+        $(
+            #[test]
+            fn annotation_laws_cached_matches_fresh_after_update() {
+                use $crate::{Annotated, Annotation, DeltaAnnotation};
+
+                // gives the closure its signature before its body is checked
+                fn update<F>(f: F) -> F
+                where
+                    F: FnMut(
+                        &mut $child,
+                    )
+                        -> <$anno as DeltaAnnotation<$child>>::Delta,
+                {
+                    f
+                }
+                let mut update = update($update);
+
+                for tree in $trees {
+                    let mut annotated = Annotated::<$child, $anno>::new(tree);
+                    for _ in 0..3 {
+                        annotated.force();
+                        annotated.update(&mut update);
+                        assert!(annotated.is_computed());
+
+                        let fresh = <$anno as Annotation<$child>>::from_child(
+                            annotated.child(),
+                        );
+                        assert_eq!(*annotated.anno(), fresh);
+                    }
+                }
+            }
+        )?
+
+        $(
+            #[test]
+            fn annotation_laws_cached_matches_fresh_after_insert() {
+                use $crate::{Annotated, Annotation};
+
+                // gives the closure its signature before its body is checked
+                fn insertion<L, R, I, F>(leaves: I, f: F) -> (I, F)
+                where
+                    I: Fn() -> L,
+                    L: IntoIterator,
+                    F: FnMut(&mut $child, L::Item) -> Option<R>,
+                {
+                    (leaves, f)
+                }
+                let (leaves, mut insert) = insertion(|| $leaves, $insert);
+
+                for tree in $trees {
+                    let mut annotated = Annotated::<$child, $anno>::new(tree);
+                    for leaf in leaves().into_iter().chain(leaves()) {
+                        annotated.force();
+                        annotated.insert_with(leaf, &mut insert);
+
+                        let fresh = <$anno as Annotation<$child>>::from_child(
+                            annotated.child(),
+                        );
+                        assert_eq!(*annotated.anno(), fresh);
+                    }
+                }
+            }
+        )?
+    };
+}
+
+// Implements comparisons and hashing for an annotated child type, taking only
+// the child into account. The child is taken from the `child` field, unless
+// another one is given.
+macro_rules! impl_by_child {
+    ($ty:ident<$c:ident $(, $param:ident)*>) => {
+        $crate::macros::impl_by_child!($ty<$c $(, $param)*>, child);
+    };
+    ($ty:ident<$c:ident $(, $param:ident)*>, $field:tt) => {
+        impl<$c $(, $param)*> PartialEq for $ty<$c $(, $param)*>
+        where
+            $c: PartialEq,
+        {
+            fn eq(&self, other: &Self) -> bool {
+                PartialEq::eq(&self.$field, &other.$field)
+            }
+        }
+
+        impl<$c $(, $param)*> Eq for $ty<$c $(, $param)*>
+        where
+            $c: PartialEq + Eq,
+        {
+        }
+
+        impl<$c $(, $param)*> PartialOrd for $ty<$c $(, $param)*>
+        where
+            $c: PartialOrd,
+        {
+            fn partial_cmp(
+                &self,
+                other: &Self,
+            ) -> Option<core::cmp::Ordering> {
+                PartialOrd::partial_cmp(&self.$field, &other.$field)
+            }
+        }
+
+        impl<$c $(, $param)*> Ord for $ty<$c $(, $param)*>
+        where
+            $c: PartialOrd + Ord,
+        {
+            fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+                Ord::cmp(&self.$field, &other.$field)
+            }
+        }
+
+        impl<$c $(, $param)*> core::hash::Hash for $ty<$c $(, $param)*>
+        where
+            $c: core::hash::Hash,
+        {
+            fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+                core::hash::Hash::hash(&self.$field, state)
+            }
+        }
+    };
+}
+
+pub(crate) use impl_by_child;
+
+// Implements `Default` and `From` the child for an annotated child type,
+// creating it with a lazily computed annotation using `new`.
+macro_rules! impl_from_child {
+    ($ty:ident<$c:ident, $a:ident>, $bound:ident) => {
+        impl<$c, $a> Default for $ty<$c, $a>
+        where
+            $c: Default,
+            $a: $bound<$c>,
+        {
+            fn default() -> Self {
+                Self::new($c::default())
+            }
+        }
+
+        impl<$c, $a> From<$c> for $ty<$c, $a>
+        where
+            $a: $bound<$c>,
+        {
+            fn from(child: $c) -> Self {
+                Self::new(child)
+            }
+        }
+    };
+}
+
+pub(crate) use impl_from_child;
+
+// Defines the mutable reference to the child of an annotated child type,
+// invalidating the annotation when mutably de-referenced.
+macro_rules! child_ref_mut {
+    (
+        $(#[$meta:meta])*
+        $ref_mut:ident => $ty:ident<$c:ident $(, $param:ident)*>
+    ) => {
+        $(#[$meta])*
+        ///
+        /// If the value is mutably de-referenced, the annotation is invalidated
+        /// and will need to be re-computed.
+        #[derive(Debug)]
+        pub struct $ref_mut<'a, $c $(, $param)*> {
+            annotated: &'a mut $ty<$c $(, $param)*>,
+        }
+
+        impl<'a, $c $(, $param)*> core::ops::Deref
+            for $ref_mut<'a, $c $(, $param)*>
+        {
+            type Target = $c;
+
+            fn deref(&self) -> &Self::Target {
+                &self.annotated.child
+            }
+        }
+
+        impl<'a, $c $(, $param)*> core::ops::DerefMut
+            for $ref_mut<'a, $c $(, $param)*>
+        {
+            fn deref_mut(&mut self) -> &mut Self::Target {
+                // when de-referencing mutably, invalidate the annotation
+                self.annotated.invalidate();
+
+                &mut self.annotated.child
+            }
+        }
+    };
+}
+
+pub(crate) use child_ref_mut;
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use crate::anno::{Cardinality, Sum};
+    use crate::{combine, Annotated, Annotation, DeltaAnnotation};
+
+    struct Leaf(u64);
+
+    impl Annotation<Leaf> for Sum<u64> {
+        fn from_child(leaf: &Leaf) -> Self {
+            Sum(leaf.0)
+        }
+    }
+
+    struct Node(Vec<Annotated<Leaf, Sum<u64>>>);
+
+    impl Annotation<Node> for Sum<u64> {
+        fn from_child(node: &Node) -> Self {
+            combine::combine_children(&node.0)
+        }
+    }
+
+    impl DeltaAnnotation<Node> for Sum<u64> {
+        type Delta = (u64, u64);
+
+        fn apply_delta(&mut self, (added, removed): &(u64, u64)) {
+            self.0 = self.0 + added - removed;
+        }
+    }
+
+    fn trees() -> impl Iterator<Item = Node> {
+        (0..4).map(|n| Node((0..n).map(|k| Annotated::new(Leaf(k))).collect()))
+    }
+
+    crate::annotation_laws!(
+        Sum<u64>,
+        Node,
+        trees(),
+        mutate = |node| {
+            let last = node.0.last_mut().map(|leaf| leaf.child_mut().0 += 1);
+            if last.is_none() {
+                node.0.push(Annotated::new(Leaf(1)));
+            }
+        },
+        update = |node| {
+            let removed = node.0.pop().map_or(0, |leaf| leaf.into_child().0);
+            node.0.push(Annotated::new(Leaf(5)));
+            (5, removed)
+        },
+        insert = [3, 5, 8] => |node, leaf| {
+            let present = node.0.iter().any(|l| l.child().0 == leaf);
+            (!present).then(|| node.0.push(Annotated::new(Leaf(leaf))))
+        },
+    );
+
+    impl Annotation<Leaf> for Cardinality {
+        fn from_child(_: &Leaf) -> Self {
+            Cardinality::LEAF
+        }
+    }
+
+    crate::impl_annotation_tuples!(Leaf);
+
+    #[test]
+    fn tuples_compute_each_annotation() {
+        let leaf = Leaf(42);
+
+        assert_eq!(<(Sum<u64>,)>::from_child(&leaf), (Sum(42),));
+        assert_eq!(
+            <(Cardinality, Sum<u64>)>::from_child(&leaf),
+            (Cardinality(1), Sum(42))
+        );
+
+        type Eight = (
+            Sum<u64>,
+            Cardinality,
+            Sum<u64>,
+            Cardinality,
+            Sum<u64>,
+            Cardinality,
+            Sum<u64>,
+            Cardinality,
+        );
+        let eight = Eight::from_child(&leaf);
+        assert_eq!(eight.0, Sum(42));
+        assert_eq!(eight.7, Cardinality(1));
+    }
+
+    struct Pair<T>(T, T);
+
+    impl<T: Copy + core::ops::Add<Output = T>> Annotation<Pair<T>> for Sum<T> {
+        fn from_child(pair: &Pair<T>) -> Self {
+            Sum(pair.0 + pair.1)
+        }
+    }
+
+    impl<T> Annotation<Pair<T>> for Cardinality {
+        fn from_child(_: &Pair<T>) -> Self {
+            Cardinality(2)
+        }
+    }
+
+    crate::impl_annotation_tuples!(<T> Pair<T>);
+
+    #[test]
+    fn tuples_over_generic_child() {
+        let anno = <(Cardinality, Sum<i32>)>::from_child(&Pair(-1, 3));
+        assert_eq!(anno, (Cardinality(2), Sum(2)));
+    }
+
+    crate::numeric_annotation!(
+        /// The total of the leaves.
+        Total: u64,
+        combine = add,
+        leaf Leaf => |leaf| leaf.0,
+        leaf Pair<u64> => |pair| pair.0 + pair.1,
+    );
+
+    crate::numeric_annotation!(Deepest: u8, combine = max);
+
+    crate::numeric_annotation!(Shallowest: u8, combine = min);
+
+    #[test]
+    fn numeric_annotations_combine_children() {
+        use crate::Combine;
+
+        let leaves = [Leaf(1), Leaf(2), Leaf(3)];
+        assert_eq!(Total::from_child(&leaves[..]), Total(6));
+        assert_eq!(Total::from_child(&Pair(4, 5)), Total(9));
+        assert_eq!(Total::from_child(&[None, Some(Leaf(7))]), Total(7));
+
+        let depths = [Deepest(3), Deepest(1), Deepest::identity()];
+        let deepest =
+            depths.iter().fold(Deepest::identity(), |a, b| a.combine(b));
+        assert_eq!(deepest, Deepest(3));
+
+        let depths = [Shallowest(3), Shallowest(1), Shallowest::default()];
+        let shallowest = depths
+            .iter()
+            .fold(Shallowest::identity(), |a, b| a.combine(b));
+        assert_eq!(shallowest, Shallowest(1));
+    }
+
+    #[test]
+    fn numeric_annotations_convert_and_patch() {
+        let mut total = Total::from(5);
+        total += Total(3);
+        total -= Total(1);
+        assert_eq!(total, Total(7));
+        assert_eq!(u64::from(total + Total(1) - Total(2)), 6);
+
+        assert_eq!(Total::default(), Total(0));
+        assert_eq!(Deepest::default(), Deepest(u8::MIN));
+        assert_eq!(Shallowest::default(), Shallowest(u8::MAX));
+    }
+}
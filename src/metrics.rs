@@ -0,0 +1,133 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Contention counters for [`SyncAnnotated`].
+//!
+//! The counters are global, and shared by all annotated children regardless
+//! of their types.
+//!
+//! [`SyncAnnotated`]: crate::SyncAnnotated
+
+use core::sync::atomic::{AtomicU64, Ordering};
+
+struct Counters {
+    fast_hits: AtomicU64,
+    computations: AtomicU64,
+    lost_races: AtomicU64,
+}
+
+impl Counters {
+    const fn new() -> Self {
+        Self {
+            fast_hits: AtomicU64::new(0),
+            computations: AtomicU64::new(0),
+            lost_races: AtomicU64::new(0),
+        }
+    }
+}
+
+#[cfg(not(test))]
+fn with_counters<R>(f: impl FnOnce(&Counters) -> R) -> R {
+    static COUNTERS: Counters = Counters::new();
+    f(&COUNTERS)
+}
+
+// when testing, the counters are kept per thread so that tests running
+// concurrently don't affect each other's counts
+#[cfg(test)]
+fn with_counters<R>(f: impl FnOnce(&Counters) -> R) -> R {
+    std::thread_local! {
+        static COUNTERS: Counters = const { Counters::new() };
+    }
+    COUNTERS.with(f)
+}
+
+/// A snapshot of the contention counters.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Contention {
+    /// Number of times an annotation was asked for and was already computed.
+    pub fast_hits: u64,
+    /// Number of times an annotation was computed.
+    pub computations: u64,
+    /// Number of computations whose result was discarded, due to another
+    /// thread having computed the same annotation first.
+    pub lost_races: u64,
+}
+
+/// Returns a snapshot of the contention counters.
+pub fn contention() -> Contention {
+    with_counters(|c| Contention {
+        fast_hits: c.fast_hits.load(Ordering::Relaxed),
+        computations: c.computations.load(Ordering::Relaxed),
+        lost_races: c.lost_races.load(Ordering::Relaxed),
+    })
+}
+
+/// Reset all contention counters to zero.
+pub fn reset() {
+    with_counters(|c| {
+        c.fast_hits.store(0, Ordering::Relaxed);
+        c.computations.store(0, Ordering::Relaxed);
+        c.lost_races.store(0, Ordering::Relaxed);
+    })
+}
+
+pub(crate) fn record_fast_hit() {
+    with_counters(|c| c.fast_hits.fetch_add(1, Ordering::Relaxed));
+}
+
+pub(crate) fn record_computation() {
+    with_counters(|c| c.computations.fetch_add(1, Ordering::Relaxed));
+}
+
+pub(crate) fn record_lost_race() {
+    with_counters(|c| c.lost_races.fetch_add(1, Ordering::Relaxed));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Annotation, SyncAnnotated};
+
+    struct Double(u64);
+
+    impl Annotation<u64> for Double {
+        fn from_child(n: &u64) -> Self {
+            Double(2 * n)
+        }
+    }
+
+    fn counts(fast_hits: u64, computations: u64) -> Contention {
+        Contention {
+            fast_hits,
+            computations,
+            lost_races: 0,
+        }
+    }
+
+    #[test]
+    fn counters_track_computations_and_hits() {
+        reset();
+        let mut annotated = SyncAnnotated::<_, Double>::new(21);
+        assert_eq!(contention(), counts(0, 0));
+
+        assert_eq!(annotated.anno().0, 42);
+        assert_eq!(contention(), counts(0, 1));
+
+        assert_eq!(annotated.anno().0, 42);
+        assert_eq!(contention(), counts(1, 1));
+
+        annotated.invalidate();
+        assert_eq!(contention(), counts(1, 1));
+
+        *annotated.child_mut() = 4;
+        assert_eq!(annotated.anno().0, 8);
+        assert_eq!(contention(), counts(1, 2));
+
+        reset();
+        assert_eq!(contention(), Contention::default());
+    }
+}
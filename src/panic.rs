@@ -0,0 +1,138 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use core::any::Any;
+use core::cell::Ref;
+use core::fmt;
+
+use std::boxed::Box;
+use std::panic::{self, AssertUnwindSafe};
+use std::string::String;
+
+use crate::{Annotated, Annotation};
+
+impl<C, A> Annotated<C, A>
+where
+    A: Annotation<C>,
+{
+    /// Returns the annotation over the child, catching a panic during its
+    /// computation and returning it as an error.
+    ///
+    /// The annotation is left uncomputed on error, and will be computed again
+    /// the next time it is asked for.
+    pub fn try_anno_catching(&self) -> Result<Ref<'_, A>, AnnoPanic> {
+        if !self.is_computed() {
+            let anno = panic::catch_unwind(AssertUnwindSafe(|| {
+                A::from_child(&self.child)
+            }))
+            .map_err(|payload| AnnoPanic { payload })?;
+
+            *self.anno.borrow_mut() = Some(anno);
+        }
+
+        Ok(self.anno())
+    }
+}
+
+/// Error returned when the computation of an annotation panics.
+pub struct AnnoPanic {
+    payload: Box<dyn Any + Send>,
+}
+
+impl AnnoPanic {
+    /// Returns the payload the computation panicked with.
+    pub fn into_payload(self) -> Box<dyn Any + Send> {
+        self.payload
+    }
+
+    /// Returns the panic message, if the payload is a string.
+    pub fn message(&self) -> Option<&str> {
+        self.payload.downcast_ref::<&str>().copied().or_else(|| {
+            self.payload.downcast_ref::<String>().map(String::as_str)
+        })
+    }
+}
+
+impl fmt::Debug for AnnoPanic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AnnoPanic")
+            .field("message", &self.message())
+            .finish()
+    }
+}
+
+impl fmt::Display for AnnoPanic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.message() {
+            Some(msg) => write!(f, "annotation computation panicked: {msg}"),
+            None => write!(f, "annotation computation panicked"),
+        }
+    }
+}
+
+impl std::error::Error for AnnoPanic {}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+    use std::string::ToString;
+
+    use super::*;
+
+    struct Flaky {
+        value: u64,
+        fail: Cell<bool>,
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Value(u64);
+
+    impl Annotation<Flaky> for Value {
+        fn from_child(flaky: &Flaky) -> Self {
+            if flaky.fail.get() {
+                panic!("failed computing {}", flaky.value);
+            }
+            Value(flaky.value)
+        }
+    }
+
+    #[test]
+    fn panic_is_returned_and_not_cached() {
+        let annotated = Annotated::<_, Value>::new(Flaky {
+            value: 42,
+            fail: Cell::new(true),
+        });
+
+        let err = annotated.try_anno_catching().unwrap_err();
+        assert_eq!(err.message(), Some("failed computing 42"));
+        assert_eq!(
+            err.to_string(),
+            "annotation computation panicked: failed computing 42"
+        );
+        assert!(!annotated.is_computed());
+
+        annotated.child().fail.set(false);
+        assert_eq!(*annotated.try_anno_catching().unwrap(), Value(42));
+        assert!(annotated.is_computed());
+    }
+
+    #[test]
+    fn non_string_payload_has_no_message() {
+        struct Boom;
+
+        impl Annotation<Boom> for Value {
+            fn from_child(_: &Boom) -> Self {
+                panic::panic_any(7u8)
+            }
+        }
+
+        let annotated = Annotated::<_, Value>::new(Boom);
+
+        let err = annotated.try_anno_catching().unwrap_err();
+        assert_eq!(err.message(), None);
+        assert_eq!(err.into_payload().downcast_ref::<u8>(), Some(&7));
+    }
+}
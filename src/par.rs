@@ -0,0 +1,148 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Parallel computation of annotations using [`rayon`].
+//!
+//! Computing the annotations of sibling children is independent work, which
+//! can be spread across threads to cut the time it takes to annotate wide
+//! structures for the first time.
+
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+use crate::{
+    Annotated, Annotation, AtomicAnnotated, LockedAnnotated, SyncAnnotated,
+};
+
+/// A reference to an annotated child whose annotation can be computed from
+/// any thread.
+pub trait ForceAnno: Send {
+    /// Compute the annotation if it wasn't already.
+    fn force_anno(self);
+}
+
+impl<C, A> ForceAnno for &mut Annotated<C, A>
+where
+    C: Send,
+    A: Send + Annotation<C>,
+{
+    fn force_anno(self) {
+        self.force();
+    }
+}
+
+impl<C, A> ForceAnno for &SyncAnnotated<C, A>
+where
+    C: Sync,
+    A: Send + Sync + Annotation<C>,
+{
+    fn force_anno(self) {
+        self.anno();
+    }
+}
+
+impl<C, A> ForceAnno for &AtomicAnnotated<C, A>
+where
+    C: Sync,
+    A: Send + Sync + Annotation<C>,
+{
+    fn force_anno(self) {
+        self.anno();
+    }
+}
+
+impl<C, A> ForceAnno for &LockedAnnotated<C, A>
+where
+    C: Sync,
+    A: Send + Sync + Annotation<C>,
+{
+    fn force_anno(self) {
+        self.anno();
+    }
+}
+
+/// Extension trait for computing annotations over parallel iterators.
+pub trait ParAnnotation: ParallelIterator {
+    /// Compute the annotations of all annotated children in parallel.
+    fn par_force_all(self)
+    where
+        Self::Item: ForceAnno,
+    {
+        self.for_each(ForceAnno::force_anno)
+    }
+}
+
+impl<I> ParAnnotation for I where I: ParallelIterator {}
+
+/// Compute the annotations of all given annotated children in parallel.
+pub fn par_force_all<I>(children: I)
+where
+    I: IntoParallelIterator,
+    I::Item: ForceAnno,
+{
+    children.into_par_iter().par_force_all()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::vec::Vec;
+
+    use rayon::iter::IntoParallelRefIterator;
+    use rayon::slice::ParallelSliceMut;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Double(u64);
+
+    impl Annotation<u64> for Double {
+        fn from_child(n: &u64) -> Self {
+            Double(2 * n)
+        }
+    }
+
+    #[test]
+    fn all_annotations_are_computed() {
+        let mut children: Vec<_> =
+            (0..64).map(Annotated::<_, Double>::new).collect();
+
+        par_force_all(&mut children);
+
+        assert!(children.iter().all(Annotated::is_computed));
+        assert_eq!(children[21].get_anno().as_deref(), Some(&Double(42)));
+    }
+
+    #[test]
+    fn shared_annotations_are_computed() {
+        let children: Vec<_> =
+            (0..64).map(SyncAnnotated::<_, Double>::new).collect();
+
+        children.par_iter().par_force_all();
+        assert!(children.iter().all(SyncAnnotated::is_computed));
+
+        let children: Vec<_> =
+            (0..64).map(AtomicAnnotated::<_, Double>::new).collect();
+
+        children.par_iter().par_force_all();
+        assert!(children.iter().all(AtomicAnnotated::is_computed));
+    }
+
+    #[test]
+    fn only_given_children_are_computed() {
+        let mut children: Vec<_> =
+            (0..64).map(Annotated::<_, Double>::new).collect();
+
+        // only every other chunk of eight children is given
+        children
+            .par_chunks_mut(8)
+            .flat_map(|chunk| chunk.into_par_iter())
+            .filter(|child| child.child() % 16 < 8)
+            .par_force_all();
+
+        for child in &children {
+            assert_eq!(child.is_computed(), child.child() % 16 < 8);
+        }
+    }
+}
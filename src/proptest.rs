@@ -0,0 +1,93 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Proptest strategies and properties for annotated structures.
+
+use core::fmt::Debug;
+
+use ::proptest::prelude::*;
+use ::proptest::test_runner::TestCaseError;
+
+use crate::{Annotated, Annotation};
+
+/// Strategy producing annotated children from a strategy over children.
+///
+/// The cache of the produced values is randomly pre-populated with the
+/// correct annotation.
+pub fn annotated<C, A, S>(child: S) -> impl Strategy<Value = Annotated<C, A>>
+where
+    S: Strategy<Value = C>,
+    C: Debug,
+    A: Debug + Annotation<C>,
+{
+    (child, any::<bool>()).prop_map(|(child, computed)| {
+        let annotated = Annotated::new(child);
+        if computed {
+            annotated.force();
+        }
+        annotated
+    })
+}
+
+/// Strategy producing recursive structures of at most the given `depth`.
+///
+/// Structures are produced from a strategy over leaves, and a function
+/// building strategies over nodes from strategies over their children.
+pub fn recursive<T, L, R, F>(
+    leaf: L,
+    depth: u32,
+    branch: F,
+) -> impl Strategy<Value = T>
+where
+    T: Debug + 'static,
+    L: Strategy<Value = T> + 'static,
+    R: Strategy<Value = T> + 'static,
+    F: Fn(BoxedStrategy<T>) -> R + 'static,
+{
+    // the desired size and expected branch size are conservative defaults,
+    // the depth being the actual bound
+    leaf.prop_recursive(depth, 256, 8, branch)
+}
+
+/// Checks that the cached annotation, if any, matches one freshly computed
+/// from the child.
+pub fn check_fresh<C, A>(
+    annotated: &Annotated<C, A>,
+) -> Result<(), TestCaseError>
+where
+    A: Debug + PartialEq + Annotation<C>,
+{
+    if let Some(cached) = annotated.get_anno() {
+        let fresh = A::from_child(annotated.child());
+        prop_assert_eq!(&*cached, &fresh);
+    }
+    Ok(())
+}
+
+/// Applies a sequence of mutations to the child, checking after each of them
+/// that the annotation matches one freshly computed from the child.
+///
+/// The annotation is computed before each mutation, ensuring it is cached
+/// when the child is mutated.
+pub fn check_mutations<C, A, M, I, F>(
+    annotated: &mut Annotated<C, A>,
+    mutations: I,
+    mut apply: F,
+) -> Result<(), TestCaseError>
+where
+    A: Debug + PartialEq + Annotation<C>,
+    I: IntoIterator<Item = M>,
+    F: FnMut(&mut C, M),
+{
+    for mutation in mutations {
+        annotated.force();
+        apply(&mut annotated.child_mut(), mutation);
+
+        annotated.force();
+        check_fresh(annotated)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,180 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Serde support for [`Annotated`].
+//!
+//! By default, only the child is serialized, and the annotation is lazily
+//! re-computed after deserialization. To also serialize the annotation
+//! alongside the child, and trust it when deserializing, use this module with
+//! `#[serde(with = "ranno::serde_anno")]`.
+
+use serde::de::{Deserialize, Deserializer};
+use serde::ser::{Serialize, Serializer};
+
+use crate::{Annotated, Annotation};
+
+impl<C, A> Serialize for Annotated<C, A>
+where
+    C: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.child().serialize(serializer)
+    }
+}
+
+impl<'de, C, A> Deserialize<'de> for Annotated<C, A>
+where
+    C: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        C::deserialize(deserializer)
+            .map(|child| Annotated::from_parts(child, None))
+    }
+}
+
+/// Serialize the child together with its annotation, computing the annotation
+/// if it wasn't already.
+pub fn serialize<C, A, S>(
+    annotated: &Annotated<C, A>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    C: Serialize,
+    A: Annotation<C> + Serialize,
+    S: Serializer,
+{
+    let (child, anno) = annotated.child_and_anno();
+    (child, &*anno).serialize(serializer)
+}
+
+/// Deserialize the child together with its annotation, trusting the
+/// annotation to be the correct one for the child.
+pub fn deserialize<'de, C, A, D>(
+    deserializer: D,
+) -> Result<Annotated<C, A>, D::Error>
+where
+    C: Deserialize<'de>,
+    A: Annotation<C> + Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    <(C, A)>::deserialize(deserializer)
+        .map(|(child, anno)| Annotated::with_anno(child, anno))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::de::{value, IntoDeserializer};
+    use serde_test::{assert_de_tokens, assert_ser_tokens, Token};
+
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Sum(u64);
+
+    impl Annotation<u64> for Sum {
+        fn from_child(n: &u64) -> Self {
+            Sum(*n)
+        }
+    }
+
+    impl Serialize for Sum {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            self.0.serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Sum {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            u64::deserialize(deserializer).map(Sum)
+        }
+    }
+
+    // serializes the annotation alongside the child, as a field using
+    // `#[serde(with = "ranno::serde_anno")]` would
+    #[derive(Debug)]
+    struct WithAnno(Annotated<u64, Sum>);
+
+    impl Serialize for WithAnno {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            serialize(&self.0, serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for WithAnno {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            deserialize(deserializer).map(WithAnno)
+        }
+    }
+
+    impl PartialEq for WithAnno {
+        fn eq(&self, other: &Self) -> bool {
+            self.0.child() == other.0.child()
+                && self.0.get_anno().as_deref() == other.0.get_anno().as_deref()
+        }
+    }
+
+    #[test]
+    fn only_child_is_serialized() {
+        let annotated = Annotated::<_, Sum>::new(42u64);
+        annotated.force();
+        assert_ser_tokens(&annotated, &[Token::U64(42)]);
+    }
+
+    #[test]
+    fn annotation_is_computed_after_deserializing() {
+        let de = IntoDeserializer::<value::Error>::into_deserializer(42u64);
+        let annotated = Annotated::<u64, Sum>::deserialize(de).unwrap();
+
+        assert!(!annotated.is_computed());
+        assert_eq!(*annotated.anno(), Sum(42));
+    }
+
+    #[test]
+    fn annotation_is_serialized_with_child() {
+        let annotated = WithAnno(Annotated::new(42u64));
+        assert_ser_tokens(
+            &annotated,
+            &[
+                Token::Tuple { len: 2 },
+                Token::U64(42),
+                Token::U64(42),
+                Token::TupleEnd,
+            ],
+        );
+    }
+
+    #[test]
+    fn deserialized_annotation_is_trusted() {
+        let annotated = WithAnno(Annotated::with_anno(42, Sum(7)));
+        assert_de_tokens(
+            &annotated,
+            &[
+                Token::Tuple { len: 2 },
+                Token::U64(42),
+                Token::U64(7),
+                Token::TupleEnd,
+            ],
+        );
+    }
+}
@@ -0,0 +1,159 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Persistence of annotated children.
+//!
+//! An annotated child can be put into a [`Store`], leaving behind a
+//! [`Persisted`] identifier together with the annotation over the child. The
+//! annotation remains available without loading the child, allowing for
+//! structures to be annotated and queried while most of their nodes are
+//! stored elsewhere, such as on disk. The child can later be resolved from
+//! the store using its identifier.
+//!
+//! # Example
+//! ```
+//! use ranno::anno::Sum;
+//! use ranno::store::Store;
+//! use ranno::{Annotated, Annotation};
+//!
+//! #[derive(Clone)]
+//! struct Values(Vec<u64>);
+//!
+//! impl Annotation<Values> for Sum<u64> {
+//!     fn from_child(values: &Values) -> Self {
+//!         Sum(values.0.iter().sum())
+//!     }
+//! }
+//!
+//! #[derive(Default)]
+//! struct VecStore(Vec<Values>);
+//!
+//! impl Store<Values> for VecStore {
+//!     type Id = usize;
+//!     type Error = &'static str;
+//!
+//!     fn put(&mut self, values: Values) -> Result<usize, Self::Error> {
+//!         self.0.push(values);
+//!         Ok(self.0.len() - 1)
+//!     }
+//!
+//!     fn get(&self, id: &usize) -> Result<Values, Self::Error> {
+//!         self.0.get(*id).cloned().ok_or("no values with the given id")
+//!     }
+//! }
+//!
+//! let mut store = VecStore::default();
+//!
+//! let annotated = Annotated::<_, Sum<u64>>::new(Values(vec![1, 2, 3]));
+//! let persisted = annotated.persist(&mut store).unwrap();
+//!
+//! assert_eq!(*persisted.anno(), Sum(6));
+//!
+//! let annotated = persisted.resolve(&store).unwrap();
+//!
+//! assert!(annotated.is_computed());
+//! assert_eq!(annotated.child().0, [1, 2, 3]);
+//! ```
+
+use crate::{Annotated, Annotation};
+
+/// A backend storing values of type `T`, identifying them by an identifier
+/// returned when they are put into it.
+pub trait Store<T> {
+    /// The identifier of a stored value.
+    type Id;
+
+    /// Error storing or loading a value.
+    type Error;
+
+    /// Put a value into the store, returning its identifier.
+    fn put(&mut self, value: T) -> Result<Self::Id, Self::Error>;
+
+    /// Get the value with the given identifier from the store.
+    fn get(&self, id: &Self::Id) -> Result<T, Self::Error>;
+}
+
+/// The identifier of a child put into a [`Store`], together with the
+/// annotation over it.
+///
+/// This `struct` is created by [`Annotated::persist`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Persisted<I, A> {
+    id: I,
+    anno: A,
+}
+
+impl<I, A> Persisted<I, A> {
+    /// Create a new persisted child from its identifier and the annotation
+    /// over it.
+    ///
+    /// The annotation is trusted to be the correct one for the child.
+    pub fn new(id: I, anno: A) -> Self {
+        Self { id, anno }
+    }
+
+    /// Returns the identifier of the child.
+    pub fn id(&self) -> &I {
+        &self.id
+    }
+
+    /// Returns the annotation over the child.
+    pub fn anno(&self) -> &A {
+        &self.anno
+    }
+
+    /// Consume the structure and return the identifier of the child and the
+    /// annotation over it.
+    pub fn split(self) -> (I, A) {
+        (self.id, self.anno)
+    }
+
+    /// Get the child from the store, annotated with the already computed
+    /// annotation.
+    pub fn resolve<C, S>(&self, store: &S) -> Result<Annotated<C, A>, S::Error>
+    where
+        S: Store<C, Id = I>,
+        A: Clone,
+    {
+        let child = store.get(&self.id)?;
+        Ok(Annotated::from_parts(child, Some(self.anno.clone())))
+    }
+}
+
+/// The annotation over a persisted child is the one kept with it.
+impl<I, A> Annotation<Persisted<I, A>> for A
+where
+    A: Clone,
+{
+    fn from_child(t: &Persisted<I, A>) -> Self {
+        t.anno.clone()
+    }
+}
+
+impl<C, A> Annotated<C, A>
+where
+    A: Annotation<C>,
+{
+    /// Put the child into the store, returning its identifier together with
+    /// the annotation over it.
+    ///
+    /// The annotation is computed before the child is stored, if it wasn't
+    /// already.
+    pub fn persist<S>(
+        self,
+        store: &mut S,
+    ) -> Result<Persisted<S::Id, A>, S::Error>
+    where
+        S: Store<C>,
+    {
+        let anno = match self.anno.into_inner() {
+            Some(anno) => anno,
+            None => A::from_child(&self.child),
+        };
+        let id = store.put(self.child)?;
+        Ok(Persisted { id, anno })
+    }
+}
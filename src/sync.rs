@@ -0,0 +1,154 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use core::mem;
+
+use std::sync::OnceLock;
+
+use crate::Annotation;
+
+/// A child annotated with some metadata, safe to share between threads.
+///
+/// This is the thread-safe counterpart to [`Annotated`], with annotations
+/// lazily computed the first time they are asked for using [`anno`], from
+/// any thread. Reading an already computed annotation never blocks.
+///
+/// [`Annotated`]: crate::Annotated
+/// [`anno`]: SyncAnnotated::anno
+#[derive(Debug)]
+pub struct SyncAnnotated<C, A> {
+    child: C,
+    anno: OnceLock<A>,
+}
+
+impl<C, A> SyncAnnotated<C, A> {
+    /// Returns the annotated child.
+    pub fn child(&self) -> &C {
+        &self.child
+    }
+
+    /// Returns the annotation over the child, if it was already computed.
+    pub fn get_anno(&self) -> Option<&A> {
+        self.anno.get()
+    }
+
+    /// Returns true if the annotation over the child is already computed.
+    pub fn is_computed(&self) -> bool {
+        self.anno.get().is_some()
+    }
+
+    /// Invalidate the annotation, forcing it to be re-computed the next time
+    /// it is asked for.
+    pub fn invalidate(&mut self) {
+        self.anno.take();
+    }
+
+    /// Replace the child with a new one, returning the old child.
+    ///
+    /// The annotation is invalidated.
+    pub fn replace_child(&mut self, child: C) -> C {
+        self.invalidate();
+        mem::replace(&mut self.child, child)
+    }
+
+    /// Consume the structure and return the child and the annotation, if it
+    /// was already computed.
+    pub fn split(self) -> (C, Option<A>) {
+        (self.child, self.anno.into_inner())
+    }
+
+    /// Consume the structure and return the child, discarding the annotation.
+    pub fn into_child(self) -> C {
+        self.child
+    }
+}
+
+impl<C, A> SyncAnnotated<C, A>
+where
+    A: Annotation<C>,
+{
+    /// Create a new annotation over a child.
+    pub fn new(child: C) -> Self {
+        Self {
+            anno: OnceLock::new(),
+            child,
+        }
+    }
+
+    /// Create a new annotated child with an already computed annotation.
+    ///
+    /// The annotation is trusted to be the correct one for the child, and
+    /// will not be recomputed until the child is mutated.
+    pub fn with_anno(child: C, anno: A) -> Self {
+        Self {
+            anno: OnceLock::from(anno),
+            child,
+        }
+    }
+
+    /// Returns the annotation over the child.
+    ///
+    /// Threads racing to compute the annotation do so without blocking each
+    /// other. The first one to finish stores its result, which is then used
+    /// by all the others, discarding their own.
+    ///
+    /// This trades CPU time for latency: every thread that asks for the
+    /// annotation before it is stored runs a full computation of it, so an
+    /// expensive annotation may be computed as many times as there are
+    /// threads racing for it. When computations should instead happen
+    /// exactly once, use [`AtomicAnnotated`] or [`LockedAnnotated`], whose
+    /// racing threads wait for a single computation to finish.
+    ///
+    /// [`AtomicAnnotated`]: crate::AtomicAnnotated
+    /// [`LockedAnnotated`]: crate::LockedAnnotated
+    pub fn anno(&self) -> &A {
+        if let Some(anno) = self.anno.get() {
+            #[cfg(feature = "metrics")]
+            crate::metrics::record_fast_hit();
+            return anno;
+        }
+
+        let anno = A::from_child(&self.child);
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_computation();
+
+        if self.anno.set(anno).is_err() {
+            #[cfg(feature = "metrics")]
+            crate::metrics::record_lost_race();
+        }
+
+        // unwrapping is ok since the annotation was just set, either by this
+        // thread or by the one that won the race
+        self.anno.get().unwrap()
+    }
+
+    /// Returns a mutable reference to the annotated child.
+    pub fn child_mut(&mut self) -> SyncAnnotatedRefMut<'_, C, A> {
+        SyncAnnotatedRefMut { annotated: self }
+    }
+}
+
+impl<C, A> Clone for SyncAnnotated<C, A>
+where
+    C: Clone,
+    A: Clone,
+{
+    fn clone(&self) -> Self {
+        // the annotation is cloned as well, to avoid re-computing it
+        Self {
+            child: self.child.clone(),
+            anno: self.anno.clone(),
+        }
+    }
+}
+
+crate::macros::impl_by_child!(SyncAnnotated<C, A>);
+crate::macros::impl_from_child!(SyncAnnotated<C, A>, Annotation);
+
+crate::macros::child_ref_mut! {
+    /// A mutable reference to a thread-safe annotated child.
+    SyncAnnotatedRefMut => SyncAnnotated<C, A>
+}
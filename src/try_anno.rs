@@ -0,0 +1,218 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use core::cell::{Ref, RefCell};
+use core::mem;
+
+/// Annotation over a child whose computation may fail.
+pub trait TryAnnotation<C>: Sized {
+    /// Error returned when the annotation can't be computed.
+    type Error;
+
+    /// Try to compute the annotation from the child.
+    fn try_from_child(t: &C) -> Result<Self, Self::Error>;
+}
+
+/// A child annotated with some metadata whose computation may fail.
+///
+/// This exposes the same API as [`Annotated`], with the annotation lazily
+/// computed the first time it is asked for using [`try_anno`]. Failures are
+/// not cached, and the computation is attempted again the next time the
+/// annotation is asked for.
+///
+/// [`Annotated`]: crate::Annotated
+/// [`try_anno`]: TryAnnotated::try_anno
+#[derive(Debug)]
+pub struct TryAnnotated<C, A> {
+    child: C,
+    anno: RefCell<Option<A>>,
+}
+
+impl<C, A> TryAnnotated<C, A> {
+    /// Returns the annotated child.
+    pub fn child(&self) -> &C {
+        &self.child
+    }
+
+    /// Returns the annotation over the child, if it was already computed.
+    pub fn get_anno(&self) -> Option<Ref<'_, A>> {
+        Ref::filter_map(self.anno.borrow(), Option::as_ref).ok()
+    }
+
+    /// Returns true if the annotation over the child is already computed.
+    pub fn is_computed(&self) -> bool {
+        self.anno.borrow().is_some()
+    }
+
+    /// Invalidate the annotation, forcing it to be re-computed the next time
+    /// it is asked for.
+    pub fn invalidate(&mut self) {
+        *self.anno.get_mut() = None;
+    }
+
+    /// Replace the child with a new one, returning the old child.
+    ///
+    /// The annotation is invalidated.
+    pub fn replace_child(&mut self, child: C) -> C {
+        self.invalidate();
+        mem::replace(&mut self.child, child)
+    }
+
+    /// Consume the structure and return the child and the annotation, if it
+    /// was already computed.
+    pub fn split(self) -> (C, Option<A>) {
+        (self.child, self.anno.into_inner())
+    }
+
+    /// Consume the structure and return the child, discarding the annotation.
+    pub fn into_child(self) -> C {
+        self.child
+    }
+}
+
+impl<C, A> TryAnnotated<C, A>
+where
+    A: TryAnnotation<C>,
+{
+    /// Create a new annotation over a child.
+    pub fn new(child: C) -> Self {
+        Self {
+            anno: RefCell::new(None),
+            child,
+        }
+    }
+
+    /// Create a new annotated child with an already computed annotation.
+    ///
+    /// The annotation is trusted to be the correct one for the child, and
+    /// will not be recomputed until the child is mutated.
+    pub fn with_anno(child: C, anno: A) -> Self {
+        Self {
+            anno: RefCell::new(Some(anno)),
+            child,
+        }
+    }
+
+    /// Returns the annotation over the child, or the error encountered while
+    /// computing it.
+    ///
+    /// # Panics
+    /// If the annotation is computed while a reference to it is alive. This
+    /// can only happen if [`TryAnnotation::try_from_child`] re-enters the
+    /// same `TryAnnotated`.
+    pub fn try_anno(&self) -> Result<Ref<'_, A>, A::Error> {
+        if !self.is_computed() {
+            let anno = A::try_from_child(&self.child)?;
+            *self.anno.borrow_mut() = Some(anno);
+        }
+
+        // unwrapping is ok since the annotation was just computed
+        Ok(self.get_anno().unwrap())
+    }
+
+    /// Returns a mutable reference to the annotated child.
+    pub fn child_mut(&mut self) -> TryAnnotatedRefMut<'_, C, A> {
+        TryAnnotatedRefMut { annotated: self }
+    }
+}
+
+impl<C, A> Clone for TryAnnotated<C, A>
+where
+    C: Clone,
+    A: Clone,
+{
+    fn clone(&self) -> Self {
+        // the annotation is cloned as well, to avoid re-computing it
+        Self {
+            child: self.child.clone(),
+            anno: self.anno.clone(),
+        }
+    }
+}
+
+crate::macros::impl_by_child!(TryAnnotated<C, A>);
+crate::macros::impl_from_child!(TryAnnotated<C, A>, TryAnnotation);
+
+crate::macros::child_ref_mut! {
+    /// A mutable reference to a fallibly annotated child.
+    TryAnnotatedRefMut => TryAnnotated<C, A>
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+
+    use super::*;
+
+    /// A child whose annotation fails to compute a number of times.
+    struct Flaky {
+        value: u64,
+        failures: Cell<usize>,
+        attempts: Cell<usize>,
+    }
+
+    impl Flaky {
+        fn new(value: u64, failures: usize) -> Self {
+            Self {
+                value,
+                failures: Cell::new(failures),
+                attempts: Cell::new(0),
+            }
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Value(u64);
+
+    #[derive(Debug, PartialEq)]
+    struct Unavailable;
+
+    impl TryAnnotation<Flaky> for Value {
+        type Error = Unavailable;
+
+        fn try_from_child(flaky: &Flaky) -> Result<Self, Unavailable> {
+            flaky.attempts.set(flaky.attempts.get() + 1);
+            match flaky.failures.get() {
+                0 => Ok(Value(flaky.value)),
+                n => {
+                    flaky.failures.set(n - 1);
+                    Err(Unavailable)
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn failures_are_retried() {
+        let annotated = TryAnnotated::<_, Value>::new(Flaky::new(42, 1));
+
+        assert_eq!(annotated.try_anno().unwrap_err(), Unavailable);
+        assert!(!annotated.is_computed());
+        assert!(annotated.get_anno().is_none());
+
+        assert_eq!(*annotated.try_anno().unwrap(), Value(42));
+        assert_eq!(annotated.child().attempts.get(), 2);
+    }
+
+    #[test]
+    fn successes_are_cached() {
+        let annotated = TryAnnotated::<_, Value>::new(Flaky::new(42, 0));
+
+        assert_eq!(*annotated.try_anno().unwrap(), Value(42));
+        assert_eq!(*annotated.try_anno().unwrap(), Value(42));
+        assert_eq!(annotated.child().attempts.get(), 1);
+    }
+
+    #[test]
+    fn mutation_invalidates_annotation() {
+        let mut annotated = TryAnnotated::<_, Value>::new(Flaky::new(42, 0));
+        annotated.try_anno().unwrap();
+
+        annotated.child_mut().value = 7;
+        assert!(!annotated.is_computed());
+        assert_eq!(*annotated.try_anno().unwrap(), Value(7));
+    }
+}
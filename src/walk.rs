@@ -0,0 +1,138 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Annotation-guided descent through [`Compound`] structures.
+//!
+//! A [`Walker`] is shown the annotations over the children of each node,
+//! starting from the root, and chooses which one to descend into, until a
+//! leaf is reached. This allows for searches to be expressed declaratively,
+//! and re-used across structures annotated in the same way.
+//!
+//! Queries reaching more than one leaf, such as range queries, are instead
+//! expressed as iterators skipping the children whose annotations rule them
+//! out.
+//!
+#![cfg_attr(
+    feature = "alloc",
+    doc = r##"
+# Example
+```
+use ranno::anno::Max;
+use ranno::compound::{ChildOf, ChildRef, Compound};
+use ranno::walk::{Step, Walker};
+use ranno::{combine, Annotated, Annotation, Branch};
+
+struct Leaf(u64);
+
+enum Tree {
+    Leaf(Leaf),
+    Node(Vec<Annotated<Tree, Max<u64>>>),
+}
+
+impl Annotation<Leaf> for Max<u64> {
+    fn from_child(leaf: &Leaf) -> Self {
+        Max::from_leaf(&leaf.0)
+    }
+}
+
+impl Annotation<Tree> for Max<u64> {
+    fn from_child(tree: &Tree) -> Self {
+        match tree {
+            Tree::Leaf(leaf) => Max::from_child(leaf),
+            Tree::Node(children) => combine::combine_children(children),
+        }
+    }
+}
+
+impl Compound<Max<u64>> for Tree {
+    type Leaf = Leaf;
+    type Node = Tree;
+
+    fn child(&self, i: usize) -> Option<ChildOf<'_, Self, Max<u64>>> {
+        match self {
+            Tree::Leaf(leaf) => (i == 0).then_some(ChildRef::Leaf(leaf)),
+            Tree::Node(nodes) => nodes.get(i).map(ChildRef::Node),
+        }
+    }
+}
+
+/// Descends into the child containing the largest leaf.
+struct Largest;
+
+impl<C> Walker<C, Max<u64>> for Largest {
+    fn walk<'a>(
+        &mut self,
+        children: impl Iterator<Item = &'a Max<u64>>,
+    ) -> Step {
+        children
+            .enumerate()
+            .max_by_key(|(_, max)| max.get())
+            .map_or(Step::Abort, |(i, _)| Step::Found(i))
+    }
+}
+
+let leaf = |n| Annotated::new(Tree::Leaf(Leaf(n)));
+let tree = Tree::Node(vec![
+    Annotated::new(Tree::Node(vec![leaf(3), leaf(9)])),
+    leaf(7),
+]);
+
+let branch = Branch::walk(&tree, Largest).unwrap();
+assert_eq!(branch.0, 9);
+```
+"##
+)]
+//!
+//! [`Compound`]: crate::Compound
+
+mod find_key;
+#[cfg(feature = "alloc")]
+mod fold;
+mod nth;
+#[cfg(feature = "alloc")]
+mod range;
+
+pub use find_key::FindKey;
+#[cfg(feature = "alloc")]
+pub use fold::{fold_annotated, Visit};
+pub use nth::Nth;
+#[cfg(feature = "alloc")]
+pub use range::RangeWalker;
+
+/// The decision taken by a [`Walker`] when shown the children of a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Step {
+    /// Descend into the child at the given index.
+    Found(usize),
+    /// Stop walking, without reaching a leaf.
+    Abort,
+}
+
+/// Chooses which child of each node to descend into, using the annotations
+/// over them.
+///
+/// The annotations over leaves are computed from the leaves themselves, so
+/// walking a structure requires `A` to be an annotation over both its nodes
+/// and its leaves.
+pub trait Walker<C, A> {
+    /// Choose a child to descend into, given the annotations over each of
+    /// the children of a node, in order.
+    fn walk<'a>(&mut self, children: impl Iterator<Item = &'a A>) -> Step
+    where
+        A: 'a;
+}
+
+impl<C, A, W> Walker<C, A> for &mut W
+where
+    W: Walker<C, A> + ?Sized,
+{
+    fn walk<'a>(&mut self, children: impl Iterator<Item = &'a A>) -> Step
+    where
+        A: 'a,
+    {
+        W::walk(self, children)
+    }
+}
@@ -0,0 +1,127 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use core::marker::PhantomData;
+
+use crate::anno::MaxKey;
+use crate::component::{AnnoComponent, Borrowed};
+use crate::walk::{Step, Walker};
+
+/// Walker descending to the leaf with a given key in a structure whose leaves
+/// are ordered by key, using the [`MaxKey`] component of its annotations.
+///
+/// The walker descends into the first child whose maximum key is not smaller
+/// than the given key, reaching the first leaf whose key is not smaller than
+/// it. The key of the leaf should be compared with the given one to check
+/// for an exact match.
+///
+/// The second type parameter is the index of the component in the
+/// annotation, and is inferred by the compiler.
+///
+#[cfg_attr(
+    feature = "alloc",
+    doc = r##"
+# Example
+```
+use ranno::anno::{Keyed, MaxKey};
+use ranno::compound::{ChildOf, ChildRef, Compound};
+use ranno::walk::FindKey;
+use ranno::{combine, Annotated, Annotation, Branch};
+
+struct Entry(u32, &'static str);
+
+impl Keyed<u32> for Entry {
+    fn key(&self) -> &u32 {
+        &self.0
+    }
+}
+
+enum Tree {
+    Leaf(Entry),
+    Node(Vec<Annotated<Tree, MaxKey<u32>>>),
+}
+
+impl Annotation<Entry> for MaxKey<u32> {
+    fn from_child(entry: &Entry) -> Self {
+        MaxKey::from_leaf(entry)
+    }
+}
+
+impl Annotation<Tree> for MaxKey<u32> {
+    fn from_child(tree: &Tree) -> Self {
+        match tree {
+            Tree::Leaf(entry) => MaxKey::from_child(entry),
+            Tree::Node(children) => combine::combine_children(children),
+        }
+    }
+}
+
+impl Compound<MaxKey<u32>> for Tree {
+    type Leaf = Entry;
+    type Node = Tree;
+
+    fn child(&self, i: usize) -> Option<ChildOf<'_, Self, MaxKey<u32>>> {
+        match self {
+            Tree::Leaf(entry) => (i == 0).then_some(ChildRef::Leaf(entry)),
+            Tree::Node(nodes) => nodes.get(i).map(ChildRef::Node),
+        }
+    }
+}
+
+let entry = |k, v| Annotated::new(Tree::Leaf(Entry(k, v)));
+let tree = Tree::Node(vec![
+    Annotated::new(Tree::Node(vec![entry(1, "one"), entry(3, "three")])),
+    Annotated::new(Tree::Node(vec![entry(5, "five"), entry(8, "eight")])),
+]);
+
+let get = |key| {
+    Branch::walk(&tree, FindKey::new(key))
+        .filter(|branch| branch.0 == key)
+        .map(|branch| branch.1)
+};
+
+assert_eq!(get(5), Some("five"));
+assert_eq!(get(4), None);
+assert_eq!(get(9), None);
+```
+"##
+)]
+#[derive(Debug)]
+pub struct FindKey<K, I = Borrowed> {
+    key: K,
+    index: PhantomData<I>,
+}
+
+impl<K, I> FindKey<K, I> {
+    /// Create a walker descending to the leaf with the given key.
+    pub fn new(key: K) -> Self {
+        Self {
+            key,
+            index: PhantomData,
+        }
+    }
+}
+
+impl<C, A, K, I> Walker<C, A> for FindKey<K, I>
+where
+    A: AnnoComponent<MaxKey<K>, I>,
+    K: Ord,
+{
+    fn walk<'a>(&mut self, children: impl Iterator<Item = &'a A>) -> Step
+    where
+        A: 'a,
+    {
+        for (i, anno) in children.enumerate() {
+            if let MaxKey::Maximum(max) = anno.component() {
+                if *max >= self.key {
+                    return Step::Found(i);
+                }
+            }
+        }
+
+        Step::Abort
+    }
+}
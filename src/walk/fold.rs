@@ -0,0 +1,155 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use core::borrow::Borrow;
+use core::ops::ControlFlow;
+
+use crate::compound::{ChildRef, Compound};
+use crate::Annotation;
+
+/// What to do with a child node when folding over a structure using
+/// [`fold_annotated`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Visit {
+    /// Descend into the node, folding over its children.
+    Descend,
+    /// Skip the node, either because it is irrelevant or because it was
+    /// accounted for using its annotation.
+    Skip,
+    /// Stop folding, returning the accumulator as is.
+    Stop,
+}
+
+/// Fold over a [`Compound`] structure, using the annotations over its child
+/// nodes to decide whether to descend into them.
+///
+/// The structure is traversed in depth-first order. Each child node is shown
+/// to `prune` together with the accumulator, which decides whether to
+/// descend into it or skip it, possibly folding its annotation into the
+/// accumulator to account for the whole node at once. Each leaf reached is
+/// shown to `leaf`, which may break to stop folding early.
+///
+/// # Example
+/// ```
+/// use core::ops::ControlFlow;
+///
+/// use ranno::anno::{Range, Sum};
+/// use ranno::compound::{ChildOf, ChildRef, Compound};
+/// use ranno::walk::{fold_annotated, Visit};
+/// use ranno::{combine, Annotated, Annotation};
+///
+/// type Anno = (Range<u64>, Sum<u64>);
+///
+/// struct Leaf(u64);
+///
+/// enum Tree {
+///     Leaf(Leaf),
+///     Node(Vec<Annotated<Tree, Anno>>),
+/// }
+///
+/// impl Annotation<Leaf> for Anno {
+///     fn from_child(leaf: &Leaf) -> Self {
+///         (Range::from_leaf(&leaf.0), Sum::from_leaf(&leaf.0))
+///     }
+/// }
+///
+/// impl Annotation<Tree> for Anno {
+///     fn from_child(tree: &Tree) -> Self {
+///         match tree {
+///             Tree::Leaf(leaf) => Anno::from_child(leaf),
+///             Tree::Node(children) => combine::combine_children(children),
+///         }
+///     }
+/// }
+///
+/// impl Compound<Anno> for Tree {
+///     type Leaf = Leaf;
+///     type Node = Tree;
+///
+///     fn child(&self, i: usize) -> Option<ChildOf<'_, Self, Anno>> {
+///         match self {
+///             Tree::Leaf(leaf) => (i == 0).then_some(ChildRef::Leaf(leaf)),
+///             Tree::Node(nodes) => nodes.get(i).map(ChildRef::Node),
+///         }
+///     }
+/// }
+///
+/// let node = |range: std::ops::Range<u64>| {
+///     let leaves = range.map(|n| Annotated::new(Tree::Leaf(Leaf(n))));
+///     Annotated::new(Tree::Node(leaves.collect()))
+/// };
+/// let tree = Tree::Node(vec![node(0..4), node(4..8), node(8..12)]);
+///
+/// // sum the leaves between 2 and 9, inclusively
+/// let (start, end) = (2, 9);
+/// let sum = fold_annotated(
+///     &tree,
+///     0,
+///     |sum, (range, node_sum): &Anno| match range.0 {
+///         Some((min, max)) if start <= min && max <= end => {
+///             *sum += node_sum.0;
+///             Visit::Skip
+///         }
+///         _ if range.overlaps(&start, &end) => Visit::Descend,
+///         _ => Visit::Skip,
+///     },
+///     |sum, leaf| {
+///         if (start..=end).contains(&leaf.0) {
+///             *sum += leaf.0;
+///         }
+///         ControlFlow::Continue(())
+///     },
+/// );
+///
+/// assert_eq!(sum, (2..=9).sum::<u64>());
+/// ```
+pub fn fold_annotated<C, A, B, P, L>(
+    root: &C,
+    init: B,
+    mut prune: P,
+    mut leaf: L,
+) -> B
+where
+    C: Compound<A>,
+    A: Annotation<C::Node>,
+    P: FnMut(&mut B, &A) -> Visit,
+    L: FnMut(&mut B, &C::Leaf) -> ControlFlow<()>,
+{
+    let mut acc = init;
+    let mut stack = Vec::from([(root, 0)]);
+
+    while let Some((node, index)) = stack.last_mut() {
+        let child = node.child(*index);
+        *index += 1;
+
+        match child {
+            Some(ChildRef::Leaf(l)) => {
+                if leaf(&mut acc, l).is_break() {
+                    break;
+                }
+            }
+            Some(ChildRef::Node(annotated)) => {
+                let visit = prune(&mut acc, &annotated.anno());
+                match visit {
+                    Visit::Descend => {
+                        stack.push((annotated.child().borrow(), 0));
+                    }
+                    Visit::Skip => {}
+                    Visit::Stop => break,
+                }
+            }
+            None => {
+                stack.pop();
+            }
+        }
+    }
+
+    acc
+}
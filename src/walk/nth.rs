@@ -0,0 +1,115 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use core::marker::PhantomData;
+
+use crate::anno::Cardinality;
+use crate::component::{AnnoComponent, Borrowed};
+use crate::walk::{Step, Walker};
+
+/// Walker descending to the leaf at a given position in a structure, using
+/// the [`Cardinality`] component of its annotations.
+///
+/// The second type parameter is the index of the component in the
+/// annotation, and is inferred by the compiler.
+///
+#[cfg_attr(
+    feature = "alloc",
+    doc = r##"
+# Example
+```
+use ranno::anno::{Cardinality, Sum};
+use ranno::compound::{ChildOf, ChildRef, Compound};
+use ranno::walk::Nth;
+use ranno::{combine, Annotated, Annotation, Branch};
+
+type Anno = (Cardinality, Sum<u64>);
+
+struct Leaf(u64);
+
+enum Tree {
+    Leaf(Leaf),
+    Node(Vec<Annotated<Tree, Anno>>),
+}
+
+impl Annotation<Leaf> for Anno {
+    fn from_child(leaf: &Leaf) -> Self {
+        (Cardinality::from_leaf(leaf), Sum::from_leaf(&leaf.0))
+    }
+}
+
+impl Annotation<Tree> for Anno {
+    fn from_child(tree: &Tree) -> Self {
+        match tree {
+            Tree::Leaf(leaf) => Anno::from_child(leaf),
+            Tree::Node(children) => combine::combine_children(children),
+        }
+    }
+}
+
+impl Compound<Anno> for Tree {
+    type Leaf = Leaf;
+    type Node = Tree;
+
+    fn child(&self, i: usize) -> Option<ChildOf<'_, Self, Anno>> {
+        match self {
+            Tree::Leaf(leaf) => (i == 0).then_some(ChildRef::Leaf(leaf)),
+            Tree::Node(nodes) => nodes.get(i).map(ChildRef::Node),
+        }
+    }
+}
+
+let leaf = |n| Annotated::new(Tree::Leaf(Leaf(n)));
+let tree = Tree::Node(vec![
+    Annotated::new(Tree::Node(vec![leaf(10), leaf(11)])),
+    Annotated::new(Tree::Node(vec![leaf(12), leaf(13), leaf(14)])),
+]);
+
+let branch = Branch::walk(&tree, Nth::new(3)).unwrap();
+assert_eq!(branch.0, 13);
+
+assert!(Branch::walk(&tree, Nth::new(5)).is_none());
+```
+"##
+)]
+#[derive(Debug)]
+pub struct Nth<I = Borrowed> {
+    n: usize,
+    index: PhantomData<I>,
+}
+
+impl<I> Nth<I> {
+    /// Create a walker descending to the leaf at position `n`, counting from
+    /// zero.
+    pub fn new(n: usize) -> Self {
+        Self {
+            n,
+            index: PhantomData,
+        }
+    }
+}
+
+impl<C, A, I> Walker<C, A> for Nth<I>
+where
+    A: AnnoComponent<Cardinality, I>,
+{
+    fn walk<'a>(&mut self, children: impl Iterator<Item = &'a A>) -> Step
+    where
+        A: 'a,
+    {
+        for (i, anno) in children.enumerate() {
+            let Cardinality(count) = *anno.component();
+
+            // the leaf is in this child, at the remaining position
+            if (self.n as u64) < count {
+                return Step::Found(i);
+            }
+            self.n -= count as usize;
+        }
+
+        Step::Abort
+    }
+}
@@ -0,0 +1,167 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use core::borrow::Borrow;
+use core::fmt;
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+
+use crate::anno::Range;
+use crate::component::{AnnoComponent, Borrowed};
+use crate::compound::{ChildRef, Compound};
+use crate::Annotation;
+
+/// Iterator over the leaves of a [`Compound`] structure lying within an
+/// inclusive interval, using the [`Range`] component of its annotations.
+///
+/// Child nodes whose range doesn't overlap the interval are skipped entirely,
+/// and only the leaves whose range does are yielded, in depth-first order.
+///
+/// The last type parameter is the index of the component in the annotation,
+/// and is inferred by the compiler.
+///
+/// # Example
+/// ```
+/// use ranno::anno::Range;
+/// use ranno::compound::{ChildOf, ChildRef, Compound};
+/// use ranno::walk::RangeWalker;
+/// use ranno::{combine, Annotated, Annotation};
+///
+/// struct Leaf(u32);
+///
+/// enum Tree {
+///     Leaf(Leaf),
+///     Node(Vec<Annotated<Tree, Range<u32>>>),
+/// }
+///
+/// impl Annotation<Leaf> for Range<u32> {
+///     fn from_child(leaf: &Leaf) -> Self {
+///         Range::from_leaf(&leaf.0)
+///     }
+/// }
+///
+/// impl Annotation<Tree> for Range<u32> {
+///     fn from_child(tree: &Tree) -> Self {
+///         match tree {
+///             Tree::Leaf(leaf) => Range::from_child(leaf),
+///             Tree::Node(children) => combine::combine_children(children),
+///         }
+///     }
+/// }
+///
+/// impl Compound<Range<u32>> for Tree {
+///     type Leaf = Leaf;
+///     type Node = Tree;
+///
+///     fn child(&self, i: usize) -> Option<ChildOf<'_, Self, Range<u32>>> {
+///         match self {
+///             Tree::Leaf(leaf) => (i == 0).then_some(ChildRef::Leaf(leaf)),
+///             Tree::Node(nodes) => nodes.get(i).map(ChildRef::Node),
+///         }
+///     }
+/// }
+///
+/// let leaf = |n| Annotated::new(Tree::Leaf(Leaf(n)));
+/// let tree = Tree::Node(vec![
+///     Annotated::new(Tree::Node(vec![leaf(1), leaf(2), leaf(3)])),
+///     Annotated::new(Tree::Node(vec![leaf(4), leaf(5), leaf(6)])),
+///     Annotated::new(Tree::Node(vec![leaf(7), leaf(8), leaf(9)])),
+/// ]);
+///
+/// let leaves: Vec<_> =
+///     RangeWalker::new(&tree, 3, 5).map(|leaf| leaf.0).collect();
+/// assert_eq!(leaves, [3, 4, 5]);
+/// ```
+pub struct RangeWalker<'a, C, A, T, I = Borrowed> {
+    stack: Vec<(&'a C, usize)>,
+    start: T,
+    end: T,
+    anno: PhantomData<&'a A>,
+    index: PhantomData<I>,
+}
+
+impl<'a, C, A, T, I> RangeWalker<'a, C, A, T, I> {
+    /// Create an iterator over the leaves of the structure with the given
+    /// root, lying between `start` and `end` inclusively.
+    pub fn new(root: &'a C, start: T, end: T) -> Self {
+        Self {
+            stack: Vec::from([(root, 0)]),
+            start,
+            end,
+            anno: PhantomData,
+            index: PhantomData,
+        }
+    }
+
+    /// Returns true if the range overlaps the interval.
+    fn overlaps(&self, range: &Range<T>) -> bool
+    where
+        T: Ord,
+    {
+        range.overlaps(&self.start, &self.end)
+    }
+}
+
+impl<'a, C, A, T, I> Iterator for RangeWalker<'a, C, A, T, I>
+where
+    C: Compound<A>,
+    A: Annotation<C::Leaf> + Annotation<C::Node> + AnnoComponent<Range<T>, I>,
+    T: Ord,
+{
+    type Item = &'a C::Leaf;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (node, index) = self.stack.last_mut()?;
+            let child = node.child(*index);
+            *index += 1;
+
+            match child {
+                Some(ChildRef::Leaf(leaf)) => {
+                    let anno = A::from_child(leaf);
+                    if self.overlaps(anno.component()) {
+                        return Some(leaf);
+                    }
+                }
+                Some(ChildRef::Node(annotated)) => {
+                    let anno = annotated.anno();
+                    if self.overlaps((*anno).component()) {
+                        self.stack.push((annotated.child().borrow(), 0));
+                    }
+                }
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+}
+
+impl<'a, C, A, T, I> FusedIterator for RangeWalker<'a, C, A, T, I>
+where
+    C: Compound<A>,
+    A: Annotation<C::Leaf> + Annotation<C::Node> + AnnoComponent<Range<T>, I>,
+    T: Ord,
+{
+}
+
+impl<'a, C, A, T, I> fmt::Debug for RangeWalker<'a, C, A, T, I>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path: Vec<_> = self.stack.iter().map(|(_, index)| index).collect();
+        f.debug_struct("RangeWalker")
+            .field("start", &self.start)
+            .field("end", &self.end)
+            .field("path", &path)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,343 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Encoding of annotated children into fixed size buffers.
+//!
+//! Types implementing [`EncodeInto`] have a known maximum encoded size,
+//! allowing them to be encoded into buffers on the stack without allocating.
+//!
+//! An [`Annotated`] is encoded as its child, followed by its annotation as an
+//! option. The annotation is always computed before encoding, so equal
+//! children are always encoded to the same bytes.
+//!
+//! The decoded annotation is trusted to be the correct one for the child, as
+//! with [`Annotated::with_anno`]. When decoding from an untrusted source, use
+//! [`Annotated::decode_from_verified`] instead, which verifies the top-level
+//! annotation but not those of nested children.
+
+use crate::{Annotated, Annotation};
+
+/// Error encoding or decoding a value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WireError {
+    /// The buffer is too small to encode or decode the value.
+    BufferTooSmall,
+    /// The buffer doesn't contain a valid encoding of the value.
+    InvalidData,
+}
+
+/// A value that can be encoded into a byte buffer.
+pub trait EncodeInto {
+    /// The maximum number of bytes the value can be encoded into.
+    const MAX_SIZE: usize;
+
+    /// Encode the value into the given buffer, returning the number of bytes
+    /// written.
+    fn encode_into(&self, buf: &mut [u8]) -> Result<usize, WireError>;
+}
+
+/// A value that can be decoded from a byte buffer.
+pub trait DecodeFrom: Sized {
+    /// Decode a value from the given buffer, returning it together with the
+    /// number of bytes read.
+    fn decode_from(buf: &[u8]) -> Result<(Self, usize), WireError>;
+}
+
+macro_rules! impl_wire_int {
+    ($($ty:ty),*) => {
+        $(
+            impl EncodeInto for $ty {
+                const MAX_SIZE: usize = core::mem::size_of::<$ty>();
+
+                fn encode_into(&self, buf: &mut [u8]) -> Result<usize, WireError> {
+                    let buf = buf
+                        .get_mut(..Self::MAX_SIZE)
+                        .ok_or(WireError::BufferTooSmall)?;
+                    buf.copy_from_slice(&self.to_le_bytes());
+                    Ok(Self::MAX_SIZE)
+                }
+            }
+
+            impl DecodeFrom for $ty {
+                fn decode_from(buf: &[u8]) -> Result<(Self, usize), WireError> {
+                    let mut bytes = [0u8; core::mem::size_of::<$ty>()];
+                    bytes.copy_from_slice(
+                        buf.get(..Self::MAX_SIZE)
+                            .ok_or(WireError::BufferTooSmall)?,
+                    );
+                    Ok((Self::from_le_bytes(bytes), Self::MAX_SIZE))
+                }
+            }
+        )*
+    };
+}
+
+impl_wire_int!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+impl EncodeInto for bool {
+    const MAX_SIZE: usize = 1;
+
+    fn encode_into(&self, buf: &mut [u8]) -> Result<usize, WireError> {
+        u8::from(*self).encode_into(buf)
+    }
+}
+
+impl DecodeFrom for bool {
+    fn decode_from(buf: &[u8]) -> Result<(Self, usize), WireError> {
+        match u8::decode_from(buf)? {
+            (0, n) => Ok((false, n)),
+            (1, n) => Ok((true, n)),
+            _ => Err(WireError::InvalidData),
+        }
+    }
+}
+
+impl EncodeInto for () {
+    const MAX_SIZE: usize = 0;
+
+    fn encode_into(&self, _: &mut [u8]) -> Result<usize, WireError> {
+        Ok(0)
+    }
+}
+
+impl DecodeFrom for () {
+    fn decode_from(_: &[u8]) -> Result<(Self, usize), WireError> {
+        Ok(((), 0))
+    }
+}
+
+impl<T, const N: usize> EncodeInto for [T; N]
+where
+    T: EncodeInto,
+{
+    const MAX_SIZE: usize = T::MAX_SIZE * N;
+
+    fn encode_into(&self, buf: &mut [u8]) -> Result<usize, WireError> {
+        let mut written = 0;
+        for elem in self {
+            written += elem.encode_into(&mut buf[written..])?;
+        }
+        Ok(written)
+    }
+}
+
+impl<T, const N: usize> DecodeFrom for [T; N]
+where
+    T: DecodeFrom,
+{
+    fn decode_from(buf: &[u8]) -> Result<(Self, usize), WireError> {
+        let mut read = 0;
+        let mut result = Ok(());
+
+        let arr = core::array::from_fn(|_| {
+            // after the first error, elements are filled with an arbitrary
+            // value that is never returned
+            match result.and_then(|_| T::decode_from(&buf[read..])) {
+                Ok((elem, n)) => {
+                    read += n;
+                    Some(elem)
+                }
+                Err(err) => {
+                    result = Err(err);
+                    None
+                }
+            }
+        });
+        result?;
+
+        // unwrapping is ok since all elements were decoded successfully
+        Ok((arr.map(|elem: Option<T>| elem.unwrap()), read))
+    }
+}
+
+impl<T> EncodeInto for Option<T>
+where
+    T: EncodeInto,
+{
+    const MAX_SIZE: usize = 1 + T::MAX_SIZE;
+
+    fn encode_into(&self, buf: &mut [u8]) -> Result<usize, WireError> {
+        match self {
+            None => false.encode_into(buf),
+            Some(value) => {
+                let n = true.encode_into(buf)?;
+                Ok(n + value.encode_into(&mut buf[n..])?)
+            }
+        }
+    }
+}
+
+impl<T> DecodeFrom for Option<T>
+where
+    T: DecodeFrom,
+{
+    fn decode_from(buf: &[u8]) -> Result<(Self, usize), WireError> {
+        match bool::decode_from(buf)? {
+            (false, n) => Ok((None, n)),
+            (true, n) => {
+                let (value, m) = T::decode_from(&buf[n..])?;
+                Ok((Some(value), n + m))
+            }
+        }
+    }
+}
+
+impl<C, A> EncodeInto for Annotated<C, A>
+where
+    C: EncodeInto,
+    A: EncodeInto + Annotation<C>,
+{
+    const MAX_SIZE: usize = C::MAX_SIZE + Option::<A>::MAX_SIZE;
+
+    fn encode_into(&self, buf: &mut [u8]) -> Result<usize, WireError> {
+        let (child, anno) = self.child_and_anno();
+        let n = child.encode_into(buf)?;
+        let m = true.encode_into(&mut buf[n..])?;
+        let k = anno.encode_into(&mut buf[n + m..])?;
+        Ok(n + m + k)
+    }
+}
+
+impl<C, A> DecodeFrom for Annotated<C, A>
+where
+    C: DecodeFrom,
+    A: DecodeFrom,
+{
+    fn decode_from(buf: &[u8]) -> Result<(Self, usize), WireError> {
+        let (child, n) = C::decode_from(buf)?;
+        let (anno, m) = Option::<A>::decode_from(&buf[n..])?;
+
+        Ok((Self::from_parts(child, anno), n + m))
+    }
+}
+
+impl<C, A> Annotated<C, A>
+where
+    C: DecodeFrom,
+    A: DecodeFrom + Annotation<C> + PartialEq,
+{
+    /// Decode an annotated child from the given buffer, failing if the
+    /// decoded annotation isn't the correct one for the child.
+    ///
+    /// The annotation is checked using [`Annotation::verify`]. By default,
+    /// this re-computes the annotation from the child, using the annotations
+    /// of any annotated children nested in it as they were decoded.
+    ///
+    /// Only the top-level annotation is verified. The annotations of nested
+    /// children are trusted, and must be checked separately when they come
+    /// from an untrusted source.
+    pub fn decode_from_verified(
+        buf: &[u8],
+    ) -> Result<(Self, usize), WireError> {
+        let (annotated, n) = Self::decode_from(buf)?;
+        if !annotated.verify_cache() {
+            return Err(WireError::InvalidData);
+        }
+        Ok((annotated, n))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Sum(u64);
+
+    impl Annotation<u64> for Sum {
+        fn from_child(n: &u64) -> Self {
+            Sum(*n)
+        }
+    }
+
+    impl EncodeInto for Sum {
+        const MAX_SIZE: usize = u64::MAX_SIZE;
+
+        fn encode_into(&self, buf: &mut [u8]) -> Result<usize, WireError> {
+            self.0.encode_into(buf)
+        }
+    }
+
+    impl DecodeFrom for Sum {
+        fn decode_from(buf: &[u8]) -> Result<(Self, usize), WireError> {
+            u64::decode_from(buf).map(|(n, read)| (Sum(n), read))
+        }
+    }
+
+    type Node = Annotated<u64, Sum>;
+
+    fn encode<T: EncodeInto>(value: &T) -> ([u8; 32], usize) {
+        let mut buf = [0; 32];
+        let n = value.encode_into(&mut buf).unwrap();
+        (buf, n)
+    }
+
+    fn encode_parts(child: u64, anno: Option<u64>) -> ([u8; 32], usize) {
+        let mut buf = [0; 32];
+        let n = child.encode_into(&mut buf).unwrap();
+        let m = anno.encode_into(&mut buf[n..]).unwrap();
+        (buf, n + m)
+    }
+
+    #[test]
+    fn encoding_is_independent_of_cache() {
+        let lazy = Node::new(42);
+        let forced = Node::new(42);
+        forced.force();
+
+        assert_eq!(encode(&lazy), encode(&forced));
+        assert_eq!(encode(&lazy), encode_parts(42, Some(42)));
+        assert_eq!(encode(&lazy).1, Node::MAX_SIZE);
+    }
+
+    #[test]
+    fn round_trip_keeps_annotation() {
+        let (buf, n) = encode(&Node::new(42));
+
+        let (decoded, read) = Node::decode_from(&buf[..n]).unwrap();
+        assert_eq!(read, n);
+        assert_eq!(decoded.child(), &42);
+        assert_eq!(decoded.get_anno().as_deref(), Some(&Sum(42)));
+    }
+
+    #[test]
+    fn missing_annotation_is_computed_lazily() {
+        let (buf, n) = encode_parts(42, None);
+
+        let (decoded, _) = Node::decode_from(&buf[..n]).unwrap();
+        assert!(!decoded.is_computed());
+        assert_eq!(*decoded.anno(), Sum(42));
+    }
+
+    #[test]
+    fn verified_decoding_rejects_wrong_annotation() {
+        let (buf, n) = encode_parts(42, Some(7));
+
+        let (trusted, _) = Node::decode_from(&buf[..n]).unwrap();
+        assert_eq!(*trusted.anno(), Sum(7));
+        assert_eq!(
+            Node::decode_from_verified(&buf[..n]).unwrap_err(),
+            WireError::InvalidData
+        );
+
+        let (buf, n) = encode_parts(42, Some(42));
+        let (verified, _) = Node::decode_from_verified(&buf[..n]).unwrap();
+        assert_eq!(*verified.anno(), Sum(42));
+    }
+
+    #[test]
+    fn small_buffers_are_rejected() {
+        let mut buf = [0; Node::MAX_SIZE - 1];
+        assert_eq!(
+            Node::new(42).encode_into(&mut buf),
+            Err(WireError::BufferTooSmall)
+        );
+        assert_eq!(
+            Node::decode_from(&buf).unwrap_err(),
+            WireError::BufferTooSmall
+        );
+    }
+}